@@ -0,0 +1,460 @@
+use rusqlite::{params, Connection};
+
+use lib_minesweeper::difficulty::BoardSize;
+use lib_minesweeper::leaderboard::{Entry, PlayerRating, Submission};
+use lib_minesweeper::rating::Rating;
+use lib_minesweeper::replay::{verify, ReplayBoard};
+use lib_minesweeper::score::score;
+use lib_minesweeper::BoardState;
+
+pub const MAX_PLAYER_NAME: usize = 32;
+pub const DEFAULT_LIMIT: usize = 10;
+pub const MAX_LIMIT: usize = 100;
+/// Generous for flagging, unflagging and chording every cell, and a bound on
+/// the work verifying a replay takes on this single thread.
+pub const MAX_ACTIONS_PER_CELL: usize = 4;
+/// Bytes a request's body may take, room for the longest replay a 100x100
+/// board allows.
+pub const MAX_BODY: u64 = 4 << 20;
+
+/// Verified games, kept in SQLite.
+pub struct Store {
+    connection: Connection,
+}
+
+impl Store {
+    pub fn open(path: &str) -> rusqlite::Result<Store> {
+        Store::with_connection(Connection::open(path)?)
+    }
+
+    pub fn in_memory() -> rusqlite::Result<Store> {
+        Store::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> rusqlite::Result<Store> {
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS games (
+                id INTEGER PRIMARY KEY,
+                player TEXT NOT NULL,
+                width INTEGER NOT NULL,
+                height INTEGER NOT NULL,
+                mines INTEGER NOT NULL,
+                won INTEGER NOT NULL,
+                seconds REAL NOT NULL,
+                three_bv INTEGER NOT NULL,
+                three_bv_per_second REAL NOT NULL,
+                replay TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS games_by_size
+                ON games (width, height, mines, won, seconds);
+            CREATE UNIQUE INDEX IF NOT EXISTS games_by_replay ON games (replay);
+            CREATE TABLE IF NOT EXISTS ratings (
+                player TEXT PRIMARY KEY,
+                rating REAL NOT NULL,
+                deviation REAL NOT NULL,
+                games INTEGER NOT NULL
+            );",
+        )?;
+        Ok(Store { connection })
+    }
+
+    /// Verifies `submission` by playing it again and stores it if it checks
+    /// out, updating the player's rating. Returns the entry it makes.
+    ///
+    /// Only classic games without lives or fair guesses on boards made from
+    /// a seed are ranked, so every entry was played under the same rules on
+    /// mines the player didn't place.
+    pub fn submit(&self, submission: &Submission) -> Result<Entry, SubmitError> {
+        let player = submission.player.trim();
+        if player.is_empty() || player.chars().count() > MAX_PLAYER_NAME {
+            return Err(SubmitError::BadPlayer);
+        }
+        let replay = &submission.replay;
+        // seeded boards are always classic
+        let size = match &replay.board {
+            ReplayBoard::Seeded { size, .. } => size,
+            ReplayBoard::Layout { .. } => {
+                return Err(SubmitError::Unranked("boards made from a seed"))
+            }
+        };
+        if replay.lives.is_some() {
+            return Err(SubmitError::Unranked("games without lives"));
+        }
+        // which also keeps the solver moving mines out of verification
+        if replay.fair_guesses {
+            return Err(SubmitError::Unranked("games without fair guesses"));
+        }
+        if replay.actions.len() > MAX_ACTIONS_PER_CELL * size.width * size.height {
+            return Err(SubmitError::Unranked("games with fewer actions"));
+        }
+        // written back out, so the same game can't pass as new by spacing
+        let replay = serde_json::to_string(replay).expect("replays serialize");
+        let submitted: bool = self.connection.query_row(
+            "SELECT EXISTS (SELECT 1 FROM games WHERE replay = ?1)",
+            params![replay],
+            |row| row.get(0),
+        )?;
+        if submitted {
+            return Err(SubmitError::Duplicate);
+        }
+        let verified =
+            verify(&submission.replay, &submission.claim).map_err(SubmitError::Rejected)?;
+        // seeded boards are classic, which are always rated
+        let rating = self
+            .rating(player)?
+            .rate_game(&verified.board, verified.seconds)
+            .map_err(|_| SubmitError::Unranked("classic games"))?;
+        let score = score(&verified.board, &verified.clicks, verified.seconds);
+        let size = BoardSize::of(&verified.board);
+        self.connection.execute(
+            "INSERT INTO games
+                (player, width, height, mines, won, seconds, three_bv, three_bv_per_second, replay)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                player,
+                size.width,
+                size.height,
+                size.mines,
+                verified.board.state == BoardState::Won,
+                score.seconds,
+                score.three_bv,
+                score.three_bv_per_second,
+                replay,
+            ],
+        )?;
+        self.connection.execute(
+            "INSERT OR REPLACE INTO ratings (player, rating, deviation, games)
+                VALUES (?1, ?2, ?3, ?4)",
+            params![player, rating.rating, rating.deviation, rating.games],
+        )?;
+        Ok(Entry {
+            player: player.into(),
+            size,
+            seconds: score.seconds,
+            three_bv: score.three_bv,
+            three_bv_per_second: score.three_bv_per_second,
+        })
+    }
+
+    /// The rating of `player`, or a fresh one for new players.
+    pub fn rating(&self, player: &str) -> rusqlite::Result<Rating> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT rating, deviation, games FROM ratings WHERE player = ?1")?;
+        let mut rows = statement.query(params![player])?;
+        match rows.next()? {
+            Some(row) => Ok(Rating {
+                rating: row.get(0)?,
+                deviation: row.get(1)?,
+                games: row.get(2)?,
+            }),
+            None => Ok(Rating::default()),
+        }
+    }
+
+    /// The highest rated players.
+    pub fn ratings(&self, limit: usize) -> rusqlite::Result<Vec<PlayerRating>> {
+        let mut statement = self.connection.prepare(
+            "SELECT player, rating, deviation, games FROM ratings
+                ORDER BY rating DESC, player
+                LIMIT ?1",
+        )?;
+        let ratings = statement.query_map(params![limit], |row| {
+            Ok(PlayerRating {
+                player: row.get(0)?,
+                rating: Rating {
+                    rating: row.get(1)?,
+                    deviation: row.get(2)?,
+                    games: row.get(3)?,
+                },
+            })
+        })?;
+        ratings.collect()
+    }
+
+    /// The fastest won games on boards of `size`, all of them classic and
+    /// without lives or fair guesses, as `submit` takes no others.
+    pub fn top(&self, size: &BoardSize, limit: usize) -> rusqlite::Result<Vec<Entry>> {
+        let mut statement = self.connection.prepare(
+            "SELECT player, seconds, three_bv, three_bv_per_second FROM games
+                WHERE width = ?1 AND height = ?2 AND mines = ?3 AND won
+                ORDER BY seconds, id
+                LIMIT ?4",
+        )?;
+        let entries =
+            statement.query_map(params![size.width, size.height, size.mines, limit], |row| {
+                Ok(Entry {
+                    player: row.get(0)?,
+                    size: *size,
+                    seconds: row.get(1)?,
+                    three_bv: row.get(2)?,
+                    three_bv_per_second: row.get(3)?,
+                })
+            })?;
+        entries.collect()
+    }
+}
+
+#[derive(Debug)]
+pub enum SubmitError {
+    BadPlayer,
+    /// Only these games are ranked.
+    Unranked(&'static str),
+    /// The same replay was submitted before.
+    Duplicate,
+    Rejected(lib_minesweeper::replay::ReplayError),
+    Storage(rusqlite::Error),
+}
+
+impl From<rusqlite::Error> for SubmitError {
+    fn from(e: rusqlite::Error) -> Self {
+        SubmitError::Storage(e)
+    }
+}
+
+/// What to answer a request with.
+#[derive(Debug, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    fn json(status: u16, body: &impl serde::Serialize) -> Response {
+        Response {
+            status,
+            body: serde_json::to_string(body).expect("responses serialize"),
+        }
+    }
+
+    pub fn error(status: u16, message: impl ToString) -> Response {
+        Response::json(status, &serde_json::json!({ "error": message.to_string() }))
+    }
+}
+
+/// Answers a request, independently of how it came in:
+/// - `POST /games` with a JSON `Submission` stores it if it verifies.
+/// - `GET /top?width=W&height=H&mines=M[&limit=N]` lists the fastest games.
+/// - `GET /ratings[?limit=N]` lists the highest rated players.
+pub fn handle(store: &Store, method: &str, url: &str, body: &str) -> Response {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let param = |name: &str| -> Option<usize> {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .and_then(|(_, value)| value.parse().ok())
+    };
+    let limit = param("limit").unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    match (method, path) {
+        ("POST", "/games") => {
+            let submission: Submission = match serde_json::from_str(body) {
+                Ok(submission) => submission,
+                Err(e) => return Response::error(400, e),
+            };
+            match store.submit(&submission) {
+                Ok(entry) => Response::json(201, &entry),
+                Err(SubmitError::BadPlayer) => Response::error(
+                    400,
+                    format!("player names take 1 to {} characters", MAX_PLAYER_NAME),
+                ),
+                Err(SubmitError::Unranked(ranked)) => {
+                    Response::error(422, format!("only {} are ranked", ranked))
+                }
+                Err(SubmitError::Duplicate) => {
+                    Response::error(409, "this game was submitted already")
+                }
+                Err(SubmitError::Rejected(e)) => Response::error(422, e),
+                Err(SubmitError::Storage(e)) => Response::error(500, e),
+            }
+        }
+        ("GET", "/top") => {
+            let size = match (param("width"), param("height"), param("mines")) {
+                (Some(width), Some(height), Some(mines)) => BoardSize {
+                    width,
+                    height,
+                    mines,
+                },
+                _ => return Response::error(400, "width, height and mines are needed"),
+            };
+            match store.top(&size, limit) {
+                Ok(entries) => Response::json(200, &entries),
+                Err(e) => Response::error(500, e),
+            }
+        }
+        ("GET", "/ratings") => match store.ratings(limit) {
+            Ok(ratings) => Response::json(200, &ratings),
+            Err(e) => Response::error(500, e),
+        },
+        ("OPTIONS", _) => Response {
+            status: 204,
+            body: String::new(),
+        },
+        _ => Response::error(404, "not found"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_minesweeper::replay::TimedAction;
+    use lib_minesweeper::replay::{Claim, Replay};
+    use lib_minesweeper::score::Clicks;
+    use lib_minesweeper::{create_board, numbers_on_board, seeded_rand, Action, MapElement};
+
+    const SEED: u64 = 5;
+
+    /// A seeded 5x2 board won over `millis`, flagging a mine and then
+    /// opening every cell that's still closed.
+    fn submission(player: &str, millis: i64) -> Submission {
+        let board = numbers_on_board(create_board(5, 2, 2, seeded_rand(SEED)));
+        let mut replay = Replay::seeded(&board, SEED);
+        let mut clicks = Clicks::default();
+        let mut actions = vec![Action::Flag(board.mine_layout()[0].0)];
+        let mut current = board.apply(&actions[0]).unwrap();
+        clicks.record(&actions[0], true);
+        for p in board.points() {
+            if let Some(MapElement::Number { state, .. }) = current.at(&p) {
+                if *state == lib_minesweeper::MapElementCellState::Closed {
+                    let action = Action::Open(p);
+                    current = current.apply(&action).unwrap();
+                    clicks.record(&action, true);
+                    actions.push(action);
+                }
+            }
+        }
+        assert_eq!(current.state, BoardState::Won);
+        let last = actions.len() as i64 - 1;
+        for (i, action) in actions.into_iter().enumerate() {
+            replay.record(100 + millis * i as i64 / last, action);
+        }
+        Submission {
+            player: player.into(),
+            claim: Claim {
+                state: BoardState::Won,
+                seconds: replay.seconds(),
+                clicks,
+            },
+            replay,
+        }
+    }
+
+    fn post(store: &Store, submission: &Submission) -> Response {
+        handle(
+            store,
+            "POST",
+            "/games",
+            &serde_json::to_string(submission).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_top() {
+        let store = Store::in_memory().unwrap();
+        assert_eq!(post(&store, &submission("slow", 3000)).status, 201);
+        assert_eq!(post(&store, &submission("fast", 1000)).status, 201);
+
+        let response = handle(&store, "GET", "/top?width=5&height=2&mines=2", "");
+        assert_eq!(response.status, 200);
+        let entries: Vec<Entry> = serde_json::from_str(&response.body).unwrap();
+        let players: Vec<&str> = entries.iter().map(|e| e.player.as_str()).collect();
+        assert_eq!(players, vec!["fast", "slow"]);
+        assert_eq!(entries[0].seconds, 1.0);
+
+        let response = handle(&store, "GET", "/top?width=9&height=9&mines=10", "");
+        assert_eq!(response.body, "[]");
+    }
+
+    #[test]
+    fn test_only_ranks_classic_seeded_games() {
+        let store = Store::in_memory().unwrap();
+        let fair = submission("fair", 1000);
+
+        let mut layout = fair.clone();
+        layout.replay = Replay {
+            board: lib_minesweeper::replay::ReplayBoard::of(&numbers_on_board(create_board(
+                5,
+                2,
+                2,
+                seeded_rand(SEED),
+            ))),
+            ..layout.replay
+        };
+        let response = post(&store, &layout);
+        assert_eq!(response.status, 422);
+        assert!(response
+            .body
+            .contains("only boards made from a seed are ranked"));
+
+        let mut lives = fair.clone();
+        lives.replay.lives = Some(3);
+        assert_eq!(post(&store, &lives).status, 422);
+        let mut fair_guesses = fair.clone();
+        fair_guesses.replay.fair_guesses = true;
+        assert_eq!(post(&store, &fair_guesses).status, 422);
+        let mut long = fair.clone();
+        let flag = long.replay.actions[0].clone();
+        long.replay.actions = (0..41)
+            .map(|i| TimedAction {
+                millis: 100 + i * 50,
+                ..flag.clone()
+            })
+            .collect();
+        assert_eq!(post(&store, &long).status, 422);
+
+        assert_eq!(post(&store, &fair).status, 201);
+    }
+
+    #[test]
+    fn test_ratings() {
+        let store = Store::in_memory().unwrap();
+        post(&store, &submission("fast", 1000));
+        post(&store, &submission("fast", 1200));
+        post(&store, &submission("slow", 30000));
+
+        let response = handle(&store, "GET", "/ratings", "");
+        let ratings: Vec<PlayerRating> = serde_json::from_str(&response.body).unwrap();
+        let players: Vec<(&str, u32)> = ratings
+            .iter()
+            .map(|r| (r.player.as_str(), r.rating.games))
+            .collect();
+        assert_eq!(players, vec![("fast", 2), ("slow", 1)]);
+        assert_eq!(store.rating("nobody").unwrap(), Rating::default());
+    }
+
+    #[test]
+    fn test_rejects_resubmissions() {
+        let store = Store::in_memory().unwrap();
+        assert_eq!(post(&store, &submission("twice", 1000)).status, 201);
+        // the same game only counts once, whoever sends it
+        assert_eq!(post(&store, &submission("twice", 1000)).status, 409);
+        assert_eq!(post(&store, &submission("copycat", 1000)).status, 409);
+        assert_eq!(store.rating("twice").unwrap().games, 1);
+        assert_eq!(store.rating("copycat").unwrap().games, 0);
+    }
+
+    #[test]
+    fn test_rejects_bad_submissions() {
+        let store = Store::in_memory().unwrap();
+        let mut tampered = submission("cheater", 1000);
+        tampered.claim.seconds = 0.1;
+        assert_eq!(post(&store, &tampered).status, 422);
+        assert_eq!(post(&store, &submission("  ", 1000)).status, 400);
+        assert_eq!(handle(&store, "POST", "/games", "{").status, 400);
+        assert_eq!(handle(&store, "GET", "/top?width=5", "").status, 400);
+        assert_eq!(handle(&store, "GET", "/nowhere", "").status, 404);
+        assert_eq!(
+            store
+                .top(
+                    &BoardSize {
+                        width: 5,
+                        height: 2,
+                        mines: 2
+                    },
+                    10
+                )
+                .unwrap(),
+            vec![]
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use std::io::Read;
+
+use leaderboard::{handle, Response, Store, MAX_BODY};
+use tiny_http::{Header, Server};
+
+const USAGE: &str = "usage: leaderboard [ADDRESS] [DATABASE]
+  ADDRESS   where to listen, 127.0.0.1:8037 by default
+  DATABASE  SQLite file to keep games in, leaderboard.sqlite by default";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "-h" || a == "--help") || args.len() > 2 {
+        println!("{}", USAGE);
+        return;
+    }
+    let address = args.first().map_or("127.0.0.1:8037", |a| a.as_str());
+    let database = args.get(1).map_or("leaderboard.sqlite", |a| a.as_str());
+
+    let store = Store::open(database).unwrap_or_else(|e| {
+        eprintln!("Couldn't open {}: {}", database, e);
+        std::process::exit(1);
+    });
+    let server = Server::http(address).unwrap_or_else(|e| {
+        eprintln!("Couldn't listen on {}: {}", address, e);
+        std::process::exit(1);
+    });
+    println!("Serving the leaderboard on http://{}", address);
+
+    for mut request in server.incoming_requests() {
+        // a byte over the limit is enough to tell it was hit
+        let mut body = vec![];
+        if let Err(e) = request
+            .as_reader()
+            .take(MAX_BODY + 1)
+            .read_to_end(&mut body)
+        {
+            eprintln!("Couldn't read request: {}", e);
+            continue;
+        }
+        let response = if body.len() as u64 > MAX_BODY {
+            Response::error(413, format!("bodies take at most {} bytes", MAX_BODY))
+        } else {
+            match String::from_utf8(body) {
+                Ok(body) => handle(&store, request.method().as_str(), request.url(), &body),
+                Err(e) => Response::error(400, e),
+            }
+        };
+        println!(
+            "{} {} -> {}",
+            request.method(),
+            request.url(),
+            response.status
+        );
+        // the web frontend is usually served from somewhere else
+        let headers = [
+            "Content-Type: application/json",
+            "Access-Control-Allow-Origin: *",
+            "Access-Control-Allow-Methods: GET, POST, OPTIONS",
+            "Access-Control-Allow-Headers: Content-Type",
+        ];
+        let mut reply =
+            tiny_http::Response::from_string(response.body).with_status_code(response.status);
+        for header in headers {
+            reply.add_header(header.parse::<Header>().expect("valid header"));
+        }
+        if let Err(e) = request.respond(reply) {
+            eprintln!("Couldn't respond: {}", e);
+        }
+    }
+}
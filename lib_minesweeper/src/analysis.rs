@@ -0,0 +1,105 @@
+use serde_derive::{Deserialize, Serialize};
+
+use crate::Board;
+use crate::MapElement::Mine;
+use crate::Point;
+
+/// Layout metrics of a board. They only depend on where the mines are, not on
+/// what the player has opened or flagged.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Analysis {
+    /// Bechtel's Board Benchmark Value: the least number of clicks that clears
+    /// the board without flagging, one per opening and one per isolated number.
+    pub three_bv: usize,
+    /// How many cells each opening (connected region of cells without mines
+    /// around them) reveals with a single click, its border included.
+    pub openings: Vec<usize>,
+    /// Numbers that don't border any opening, so have to be clicked one by one.
+    pub isolated_numbers: usize,
+    /// Mines over the total number of cells.
+    pub mine_density: f64,
+}
+
+pub fn analyse(board: &Board) -> Analysis {
+    let is_mine = |p: &Point| matches!(board.at(p), Some(Mine { .. }));
+    let is_empty = |p: &Point| !is_mine(p) && !board.has_surrounding_mines(p);
+
+    let mut revealed = vec![vec![false; board.width]; board.height];
+    let mut openings = vec![];
+    for start in board.points().filter(|p| is_empty(p)) {
+        if revealed[start.y as usize][start.x as usize] {
+            continue;
+        }
+        revealed[start.y as usize][start.x as usize] = true;
+        let mut size = 0;
+        let mut to_visit = vec![start];
+        while let Some(p) = to_visit.pop() {
+            size += 1;
+            if !is_empty(&p) {
+                continue;
+            }
+            for n in board.surrounding_points(&p) {
+                if !revealed[n.y as usize][n.x as usize] {
+                    revealed[n.y as usize][n.x as usize] = true;
+                    to_visit.push(n);
+                }
+            }
+        }
+        openings.push(size);
+    }
+
+    let isolated_numbers = board
+        .points()
+        .filter(|p| !is_mine(p) && !revealed[p.y as usize][p.x as usize])
+        .count();
+
+    Analysis {
+        three_bv: openings.len() + isolated_numbers,
+        openings,
+        isolated_numbers,
+        mine_density: board.mines as f64 / (board.width * board.height) as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{five_by_four_board, five_by_two_board, make_map};
+    use crate::{numbers_on_board, Board};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_analyse() {
+        let analysis = analyse(&numbers_on_board(five_by_four_board()));
+        assert_eq!(
+            analysis,
+            Analysis {
+                three_bv: 6,
+                openings: vec![8, 4],
+                isolated_numbers: 4,
+                mine_density: 0.2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_three_bv_matches_clicks_to_win() {
+        let analysis = analyse(&numbers_on_board(five_by_two_board()));
+        assert_eq!(analysis.three_bv, 3);
+        assert_eq!(analysis.openings, vec![6]);
+    }
+
+    #[test]
+    fn test_analyse_ignores_cell_state() {
+        let board = numbers_on_board(Board::new(make_map(
+            "X0N00
+             00000",
+            "FOCCC
+             CCOCC",
+        )));
+        let analysis = analyse(&board);
+        assert_eq!(analysis.openings, vec![4]);
+        assert_eq!(analysis.isolated_numbers, 4);
+        assert_eq!(analysis.three_bv, 5);
+    }
+}
@@ -0,0 +1,307 @@
+//! Recorded games as animations to share: a GIF drawn in the web's colours,
+//! or an asciicast of the board as the terminal shows it. Neither needs a
+//! display, so they can be made on a server.
+
+use std::io::{self, Write};
+
+use serde_derive::Serialize;
+
+use lib_minesweeper::svg::{background, number_colour};
+use lib_minesweeper::MapElement::{Mine, Number};
+use lib_minesweeper::MapElementCellState::{
+    Closed, Exploded, Flagged, Open, Questioned, Revealed, WronglyFlagged,
+};
+use lib_minesweeper::{Board, BoardState, MineKind, Variant};
+
+use crate::cli::render::Style;
+use crate::{board_state, map_lines};
+
+/// Pixels each cell takes, with its margin.
+const CELL_SIZE: usize = 20;
+const MARGIN: usize = 1;
+const PADDING: usize = 8;
+/// The shortest and longest a frame stays up, in hundredths of a second, so
+/// quick chords can be seen and long thinks don't drag.
+const MIN_DELAY: i64 = 10;
+const MAX_DELAY: i64 = 200;
+/// How long the end of the game stays up before the animation starts over.
+const LAST_DELAY: u16 = 300;
+
+/// A board after some move and when it was made, in milliseconds since the
+/// board was shown, as `Replay::frames` gives them.
+pub type Frame = (i64, Board);
+
+/// Characters drawn a pixel at a time, `#` for the pixels that are set.
+fn glyph(c: char) -> &'static [&'static str] {
+    match c {
+        '0' => &["###", "#.#", "#.#", "#.#", "###"],
+        '1' => &[".#.", "##.", ".#.", ".#.", "###"],
+        '2' => &["###", "..#", "###", "#..", "###"],
+        '3' => &["###", "..#", ".##", "..#", "###"],
+        '4' => &["#.#", "#.#", "###", "..#", "..#"],
+        '5' => &["###", "#..", "###", "..#", "###"],
+        '6' => &["###", "#..", "###", "#.#", "###"],
+        '7' => &["###", "..#", "..#", ".#.", ".#."],
+        '8' => &["###", "#.#", "###", "#.#", "###"],
+        '9' => &["###", "#.#", "###", "..#", "###"],
+        '-' => &["...", "...", "###", "...", "..."],
+        '·' => &[".", ".", "#", ".", "."],
+        // a mine, a flag and a cross for wrong flags
+        '*' => &[".###.", "#####", "#####", "#####", ".###."],
+        'F' => &["###..", "#####", "###..", "#....", "#...."],
+        'x' => &["#...#", ".#.#.", "..#..", ".#.#.", "#...#"],
+        _ => &["###", "..#", ".##", "...", ".#."],
+    }
+}
+
+/// `#rrggbb` as its three bytes.
+fn rgb(colour: &str) -> [u8; 3] {
+    let byte = |i: usize| u8::from_str_radix(&colour[i..i + 2], 16).expect("colours are hex");
+    [byte(1), byte(3), byte(5)]
+}
+
+fn image_size(board: &Board) -> (usize, usize) {
+    (
+        board.width * CELL_SIZE + 2 * PADDING,
+        board.height * CELL_SIZE + 2 * PADDING,
+    )
+}
+
+/// Draws boards with the colours they need, keeping one palette for all
+/// the frames of an animation.
+#[derive(Default)]
+struct Painter {
+    palette: Vec<&'static str>,
+}
+
+impl Painter {
+    fn index(&mut self, colour: &'static str) -> u8 {
+        let i = match self.palette.iter().position(|c| *c == colour) {
+            Some(i) => i,
+            None => {
+                self.palette.push(colour);
+                self.palette.len() - 1
+            }
+        };
+        i as u8
+    }
+
+    /// `board` as palette indexes, a row at a time, looking like the SVG of
+    /// it. Closed cells are left blank, as the question marks the web puts
+    /// on them don't read at this size.
+    fn paint(&mut self, board: &Board) -> Vec<u8> {
+        let (width, height) = image_size(board);
+        let mut pixels = vec![self.index(background(&board.state)); width * height];
+        let mut fill = |x: usize, y: usize, w: usize, h: usize, colour: u8| {
+            for row in pixels[y * width..(y + h) * width].chunks_mut(width) {
+                row[x..x + w].fill(colour);
+            }
+        };
+        for p in board.points() {
+            let element = board.at(&p).unwrap();
+            let x = PADDING + p.x as usize * CELL_SIZE + MARGIN;
+            let y = PADDING + p.y as usize * CELL_SIZE + MARGIN;
+            let size = CELL_SIZE - 2 * MARGIN;
+            let clickable = matches!(
+                (&board.state, element.state()),
+                (BoardState::Ready | BoardState::Playing, Closed | Questioned)
+            );
+            let button = match element.state() {
+                Exploded => background(&BoardState::Failed),
+                _ if clickable => "#e9e9e9",
+                _ => "#f9f9f9",
+            };
+            fill(x, y, size, size, self.index(button));
+            let (text, colour) = match (element, element.state()) {
+                (Mine { .. }, Exploded) => ("*".to_string(), "#666666"),
+                (Mine { .. }, _) if board.state == BoardState::Won => ("F".to_string(), "#d81159"),
+                (Mine { .. }, Open | Revealed) => ("*".to_string(), "#666666"),
+                (_, WronglyFlagged) => ("x".to_string(), "#d81159"),
+                (_, Flagged(MineKind::Positive)) => ("F".to_string(), "#d81159"),
+                (_, Flagged(MineKind::Negative)) => ("F".to_string(), number_colour(-1)),
+                (_, Flagged(MineKind::Secondary)) => ("F".to_string(), number_colour(8)),
+                (_, Closed) => continue,
+                (_, Questioned) => ("?".to_string(), "#d81159"),
+                (Number { .. }, _) if !board.has_surrounding_mines(&p) => continue,
+                (
+                    Number {
+                        count,
+                        secondary_count,
+                        ..
+                    },
+                    _,
+                ) if board.variant == Variant::TwoColour => (
+                    format!("{}·{}", count, secondary_count),
+                    number_colour(*count),
+                ),
+                (Number { count, .. }, _) => (count.to_string(), number_colour(*count)),
+            };
+            let colour = self.index(colour);
+            let glyphs: Vec<&[&str]> = text.chars().map(glyph).collect();
+            let text_width = glyphs.iter().map(|g| g[0].len() + 1).sum::<usize>() - 1;
+            // as big as fits, with a pixel to spare on each side
+            let scale = (1..=2).rev().find(|s| text_width * s < size).unwrap_or(1);
+            let mut left = x + (size - text_width * scale) / 2;
+            let top = y + (size - 5 * scale) / 2;
+            for rows in glyphs {
+                for (dy, row) in rows.iter().enumerate() {
+                    for (dx, _) in row.chars().enumerate().filter(|(_, c)| *c == '#') {
+                        fill(left + dx * scale, top + dy * scale, scale, scale, colour);
+                    }
+                }
+                left += (rows[0].len() + 1) * scale;
+            }
+        }
+        pixels
+    }
+}
+
+/// Hundredths of a second from the frame at `millis` to the one at `next`.
+fn delay(millis: i64, next: i64) -> u16 {
+    ((next - millis) / 10).clamp(MIN_DELAY, MAX_DELAY) as u16
+}
+
+/// Writes `frames` as a GIF that plays them in a loop.
+pub fn gif(frames: &[Frame], out: impl Write) -> io::Result<()> {
+    let Some((_, first)) = frames.first() else {
+        return Ok(());
+    };
+    let (width, height) = image_size(first);
+    let mut painter = Painter::default();
+    let images: Vec<Vec<u8>> = frames
+        .iter()
+        .map(|(_, board)| painter.paint(board))
+        .collect();
+    let palette: Vec<u8> = painter.palette.iter().flat_map(|c| rgb(c)).collect();
+    let mut encoder =
+        gif::Encoder::new(out, width as u16, height as u16, &palette).map_err(io::Error::other)?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(io::Error::other)?;
+    for (i, pixels) in images.into_iter().enumerate() {
+        let mut frame = gif::Frame::from_indexed_pixels(width as u16, height as u16, pixels, None);
+        frame.delay = match frames.get(i + 1) {
+            Some((next, _)) => delay(frames[i].0, *next),
+            None => LAST_DELAY,
+        };
+        encoder.write_frame(&frame).map_err(io::Error::other)?;
+    }
+    Ok(())
+}
+
+/// The first lines of an asciicast v2 file.
+#[derive(Serialize)]
+struct Header {
+    version: u8,
+    width: usize,
+    height: usize,
+    /// Long thinks are cut down to this many seconds when played.
+    idle_time_limit: f64,
+}
+
+/// How many columns `line` takes, leaving out colour codes.
+fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for c in line.chars() {
+        match c {
+            '\x1b' => in_escape = true,
+            c if in_escape => in_escape = !c.is_ascii_alphabetic(),
+            _ => width += 1,
+        }
+    }
+    width
+}
+
+/// Writes `frames` as an asciicast, each one drawn in `style` on a cleared
+/// screen at the time its move was made.
+pub fn asciicast(frames: &[Frame], style: Style, out: &mut impl Write) -> io::Result<()> {
+    let screens: Vec<Vec<String>> = frames
+        .iter()
+        .map(|(_, board)| {
+            let mut lines = vec![board_state(board, style)];
+            lines.extend(map_lines(board, style));
+            lines
+        })
+        .collect();
+    let header = Header {
+        width: screens
+            .iter()
+            .flatten()
+            .map(|line| visible_width(line))
+            .max()
+            .unwrap_or(0),
+        height: screens.iter().map(Vec::len).max().unwrap_or(0) + 1,
+        version: 2,
+        idle_time_limit: MAX_DELAY as f64 / 100.0,
+    };
+    writeln!(out, "{}", serde_json::to_string(&header)?)?;
+    let mut seconds = 0.0;
+    for ((millis, _), lines) in frames.iter().zip(screens) {
+        seconds = *millis as f64 / 1000.0;
+        let screen = format!("\x1b[2J\x1b[H{}\r\n", lines.join("\r\n"));
+        writeln!(out, "{}", serde_json::to_string(&(seconds, "o", screen))?)?;
+    }
+    // an empty event to keep the end up for a while
+    let end = seconds + LAST_DELAY as f64 / 100.0;
+    writeln!(out, "{}", serde_json::to_string(&(end, "o", ""))?)?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_minesweeper::replay::Replay;
+    use lib_minesweeper::{create_board_with_mines, numbers_on_board, Action, Point};
+    use pretty_assertions::assert_eq;
+
+    /// A game won in three moves on a 4x2 board.
+    fn frames() -> Vec<Frame> {
+        let board = numbers_on_board(create_board_with_mines(
+            4,
+            2,
+            &[(Point::new(3, 0), MineKind::Positive)],
+        ));
+        let mut replay = Replay::new(&board);
+        replay.record(500, Action::Open(Point::new(0, 0)));
+        replay.record(900, Action::Flag(Point::new(3, 0)));
+        replay.record(5000, Action::Open(Point::new(3, 1)));
+        replay.frames().unwrap()
+    }
+
+    #[test]
+    fn test_gif() {
+        let frames = frames();
+        let mut out = vec![];
+        gif(&frames, &mut out).unwrap();
+        assert!(out.starts_with(b"GIF89a"));
+        let mut decoder = gif::DecodeOptions::new().read_info(&out[..]).unwrap();
+        assert_eq!(decoder.width(), 96);
+        let mut delays = vec![];
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            delays.push(frame.delay);
+        }
+        // the long think before the last move is cut short
+        assert_eq!(delays, vec![50, 40, 200, 300]);
+    }
+
+    #[test]
+    fn test_asciicast() {
+        let mut out = vec![];
+        asciicast(&frames(), Style::Ascii, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<serde_json::Value> = out
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines[0]["version"], 2);
+        assert_eq!(lines[0]["height"], 6);
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[2][0], 0.5);
+        assert_eq!(lines[2][1], "o");
+        let last = lines[4][2].as_str().unwrap();
+        assert!(last.starts_with("\x1b[2J\x1b[HBoard is currently won\r\n"));
+        assert_eq!(lines[5][0], 8.0);
+        assert_eq!(visible_width("\x1b[31mab\x1b[0m c"), 4);
+    }
+}
@@ -0,0 +1,634 @@
+//! Command line options. Kept by hand rather than pulling in a parser, since
+//! there are only a few and the errors should point at what to fix.
+
+use std::fmt;
+
+use lib_minesweeper::difficulty::{Difficulty, DifficultyError};
+use lib_minesweeper::Variant;
+
+use crate::cli::render::Style;
+
+pub const USAGE: &str = "usage: lib_minesweeper [OPTIONS]
+
+Board:
+  --preset NAME        beginner (default), intermediate or expert
+  --width N            columns, on top of the preset
+  --height N           rows, on top of the preset
+  --mines N            mines, on top of the preset
+  --seed N             play the board this seed makes, to play it again
+
+Variants:
+  --negative           some mines take one off their neighbours' counts
+  --two-colour         some mines are counted in a second colour
+  --lives N            survive N mine hits
+  --no-question-marks  flagging twice clears the flag instead of asking
+  --fair-guesses       mines move so that forced guesses are safe
+
+Modes:
+  --daily              today's daily challenge, the same for everyone
+  --infinite           a board without edges, see how far you get
+  --prompt             type moves at a prompt instead of playing full screen
+  --render STYLE       colour, unicode, ascii or reader to describe rows in
+                       words; ascii when not writing to a terminal
+  --edit FILE          make a position by hand and save it to FILE, which
+                       is opened if it exists or made with --width and
+                       --height if not
+  --analyse FILE       show how likely each closed cell of the position in
+                       FILE is to be a mine, and whether a loss was bad luck
+  --svg FILE           print the position in FILE as an SVG image that looks
+                       like the web game
+  --replay FILE        export the game recorded in FILE, saved with the
+                       record command or from the leaderboard, as an
+                       animation with --gif or --cast
+  --batch N            let the solver play N classic boards, starting from
+                       --seed, and print a line for each game
+
+Batch:
+  --format FORMAT      csv (default) or json, one object per line
+  --solver SOLVER      guess (default) guesses when it has to, safe stops at
+                       the first guess after the opening click
+
+Replay:
+  --gif FILE           write the game as an animated GIF to FILE
+  --cast FILE          write the game as an asciicast to FILE, to play with
+                       asciinema, drawn like --render
+
+  -h, --help           show this";
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Game {
+    Classic,
+    Daily,
+    Infinite,
+    /// The solver plays this many boards without showing them.
+    Batch {
+        games: usize,
+    },
+    /// A position is made by hand, to be saved at `path`.
+    Edit {
+        path: String,
+    },
+    /// The position saved at `path` is worked out by the solver.
+    Analyse {
+        path: String,
+    },
+    /// The position saved at `path` is drawn as an SVG image.
+    Svg {
+        path: String,
+    },
+    /// The game recorded at `path` is exported as an animation.
+    Replay {
+        path: String,
+    },
+}
+
+/// How batch results are written.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+/// How the solver plays in batch mode.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Solver {
+    /// Guesses the least likely cell when nothing is certain.
+    Guess,
+    /// Stops when nothing is certain.
+    Safe,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Options {
+    pub game: Game,
+    pub difficulty: Difficulty,
+    pub seed: Option<u64>,
+    pub variant: Variant,
+    pub lives: Option<u32>,
+    pub question_marks: bool,
+    pub fair_guesses: bool,
+    /// Play at a line-based prompt even in a terminal.
+    pub prompt: bool,
+    /// How to draw the board, or `None` to pick one for the terminal.
+    pub render: Option<Style>,
+    pub format: Format,
+    pub solver: Solver,
+    /// Where to write a replay as a GIF.
+    pub gif: Option<String>,
+    /// Where to write a replay as an asciicast.
+    pub cast: Option<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            game: Game::Classic,
+            difficulty: Difficulty::Beginner,
+            seed: None,
+            variant: Variant::Classic,
+            lives: None,
+            question_marks: true,
+            fair_guesses: false,
+            prompt: false,
+            render: None,
+            format: Format::Csv,
+            solver: Solver::Guess,
+            gif: None,
+            cast: None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ArgsError {
+    /// Help was asked for, which isn't an error but stops parsing all the same.
+    Help,
+    UnknownOption(String),
+    MissingValue(&'static str),
+    BadValue {
+        option: &'static str,
+        value: String,
+    },
+    Difficulty(DifficultyError),
+    /// Two options that can't be used together.
+    Conflict(&'static str, &'static str),
+    /// An option that only means something with another one.
+    Requires(&'static str, &'static str),
+}
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArgsError::Help => write!(f, "{}", USAGE),
+            ArgsError::UnknownOption(option) => write!(f, "unknown option '{}'", option),
+            ArgsError::MissingValue(option) => write!(f, "{} needs a value", option),
+            ArgsError::BadValue { option, value } => {
+                write!(f, "'{}' isn't a valid value for {}", value, option)
+            }
+            ArgsError::Difficulty(e) => write!(f, "{}", e),
+            ArgsError::Conflict(a, b) => write!(f, "{} can't be used with {}", a, b),
+            ArgsError::Requires(a, b) => write!(f, "{} only works with {}", a, b),
+        }
+    }
+}
+
+impl From<DifficultyError> for ArgsError {
+    fn from(e: DifficultyError) -> Self {
+        ArgsError::Difficulty(e)
+    }
+}
+
+/// Reads the options out of `args`, which don't include the program name.
+/// Values go after their option, either as the next argument or after `=`.
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Options, ArgsError> {
+    let mut options = Options::default();
+    let (mut width, mut height, mut mines) = (None, None, None);
+    // the options that were given, to report conflicts by name
+    let mut given: Vec<&'static str> = vec![];
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (name, inline) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name.to_string(), Some(value.into())),
+            _ => (arg.clone(), None),
+        };
+        let mut inline = inline;
+        let mut value = |option: &'static str| -> Result<String, ArgsError> {
+            inline
+                .take()
+                .or_else(|| args.next())
+                .ok_or(ArgsError::MissingValue(option))
+        };
+        let option: &'static str = match name.as_str() {
+            "-h" | "--help" => return Err(ArgsError::Help),
+            "--preset" => {
+                let name = value("--preset")?;
+                options.difficulty = name.parse()?;
+                "--preset"
+            }
+            "--width" => {
+                width = Some(number("--width", value("--width")?)?);
+                "--width"
+            }
+            "--height" => {
+                height = Some(number("--height", value("--height")?)?);
+                "--height"
+            }
+            "--mines" => {
+                mines = Some(number("--mines", value("--mines")?)?);
+                "--mines"
+            }
+            "--seed" => {
+                options.seed = Some(number("--seed", value("--seed")?)?);
+                "--seed"
+            }
+            "--lives" => {
+                options.lives = Some(number("--lives", value("--lives")?)?);
+                "--lives"
+            }
+            "--negative" => {
+                options.variant = Variant::NegativeMines;
+                "--negative"
+            }
+            "--two-colour" => {
+                options.variant = Variant::TwoColour;
+                "--two-colour"
+            }
+            "--no-question-marks" => {
+                options.question_marks = false;
+                "--no-question-marks"
+            }
+            "--fair-guesses" => {
+                options.fair_guesses = true;
+                "--fair-guesses"
+            }
+            "--prompt" => {
+                options.prompt = true;
+                "--prompt"
+            }
+            // the bare words are what the modes used to be called
+            "--daily" | "daily" => {
+                options.game = Game::Daily;
+                "--daily"
+            }
+            "--infinite" | "infinite" => {
+                options.game = Game::Infinite;
+                "--infinite"
+            }
+            "--render" => {
+                options.render = Some(match value("--render")?.as_str() {
+                    "colour" | "color" => Style::Colour,
+                    "unicode" => Style::Unicode,
+                    "ascii" => Style::Ascii,
+                    "reader" => Style::Reader,
+                    other => {
+                        return Err(ArgsError::BadValue {
+                            option: "--render",
+                            value: other.to_string(),
+                        })
+                    }
+                });
+                "--render"
+            }
+            "--edit" => {
+                options.game = Game::Edit {
+                    path: value("--edit")?,
+                };
+                "--edit"
+            }
+            "--analyse" | "--analyze" => {
+                options.game = Game::Analyse {
+                    path: value("--analyse")?,
+                };
+                "--analyse"
+            }
+            "--svg" => {
+                options.game = Game::Svg {
+                    path: value("--svg")?,
+                };
+                "--svg"
+            }
+            "--replay" => {
+                options.game = Game::Replay {
+                    path: value("--replay")?,
+                };
+                "--replay"
+            }
+            "--gif" => {
+                options.gif = Some(value("--gif")?);
+                "--gif"
+            }
+            "--cast" => {
+                options.cast = Some(value("--cast")?);
+                "--cast"
+            }
+            "--batch" => {
+                let games = number("--batch", value("--batch")?)?;
+                options.game = Game::Batch { games };
+                "--batch"
+            }
+            "--format" => {
+                options.format = match value("--format")?.as_str() {
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    other => {
+                        return Err(ArgsError::BadValue {
+                            option: "--format",
+                            value: other.to_string(),
+                        })
+                    }
+                };
+                "--format"
+            }
+            "--solver" => {
+                options.solver = match value("--solver")?.as_str() {
+                    "guess" => Solver::Guess,
+                    "safe" => Solver::Safe,
+                    other => {
+                        return Err(ArgsError::BadValue {
+                            option: "--solver",
+                            value: other.to_string(),
+                        })
+                    }
+                };
+                "--solver"
+            }
+            _ => return Err(ArgsError::UnknownOption(arg)),
+        };
+        if inline.is_some() {
+            return Err(ArgsError::BadValue { option, value: arg });
+        }
+        given.push(option);
+    }
+
+    let conflicts: [(&'static str, &[&'static str]); 9] = [
+        ("--negative", &["--two-colour", "--fair-guesses"]),
+        ("--two-colour", &["--fair-guesses"]),
+        // daily boards are the same for everyone, so they're played plain
+        (
+            "--daily",
+            &[
+                "--seed",
+                "--negative",
+                "--two-colour",
+                "--lives",
+                "--fair-guesses",
+                "--infinite",
+            ],
+        ),
+        (
+            "--infinite",
+            &[
+                "--preset",
+                "--width",
+                "--height",
+                "--mines",
+                "--negative",
+                "--two-colour",
+                "--lives",
+                "--fair-guesses",
+            ],
+        ),
+        // the solver only knows how to play classic boards
+        (
+            "--batch",
+            &[
+                "--daily",
+                "--infinite",
+                "--prompt",
+                "--negative",
+                "--two-colour",
+            ],
+        ),
+        (
+            "--edit",
+            &[
+                "--daily",
+                "--infinite",
+                "--batch",
+                "--seed",
+                "--mines",
+                "--negative",
+                "--two-colour",
+            ],
+        ),
+        (
+            "--analyse",
+            &["--daily", "--infinite", "--batch", "--edit", "--prompt"],
+        ),
+        (
+            "--svg",
+            &[
+                "--daily",
+                "--infinite",
+                "--batch",
+                "--edit",
+                "--analyse",
+                "--prompt",
+            ],
+        ),
+        (
+            "--replay",
+            &[
+                "--daily",
+                "--infinite",
+                "--batch",
+                "--edit",
+                "--analyse",
+                "--svg",
+                "--prompt",
+            ],
+        ),
+    ];
+    for (a, others) in conflicts {
+        if let Some(b) = others
+            .iter()
+            .find(|b| given.contains(&a) && given.contains(b))
+        {
+            return Err(ArgsError::Conflict(a, b));
+        }
+    }
+    let requires = [
+        ("--format", "--batch"),
+        ("--solver", "--batch"),
+        ("--gif", "--replay"),
+        ("--cast", "--replay"),
+    ];
+    for (a, b) in requires.iter() {
+        if given.contains(a) && !given.contains(b) {
+            return Err(ArgsError::Requires(a, b));
+        }
+    }
+    if given.contains(&"--replay") && !given.contains(&"--gif") && !given.contains(&"--cast") {
+        return Err(ArgsError::Requires("--replay", "--gif or --cast"));
+    }
+
+    if width.is_some() || height.is_some() || mines.is_some() {
+        let size = options.difficulty.size();
+        // mines are put in by hand in the editor, so only the size is checked
+        let preset_mines = if given.contains(&"--edit") {
+            1
+        } else {
+            size.mines
+        };
+        options.difficulty = Difficulty::custom(
+            width.unwrap_or(size.width),
+            height.unwrap_or(size.height),
+            mines.unwrap_or(preset_mines),
+        )?;
+    }
+    Ok(options)
+}
+
+fn number<T: std::str::FromStr>(option: &'static str, value: String) -> Result<T, ArgsError> {
+    value
+        .parse()
+        .map_err(|_| ArgsError::BadValue { option, value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_minesweeper::difficulty::BoardSize;
+    use pretty_assertions::assert_eq;
+
+    fn parse_str(args: &str) -> Result<Options, ArgsError> {
+        parse(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn test_parse_defaults() {
+        assert_eq!(parse_str(""), Ok(Options::default()));
+    }
+
+    #[test]
+    fn test_parse_size() {
+        let options = parse_str("--preset expert --mines=120 --seed 7").unwrap();
+        assert_eq!(
+            options.difficulty,
+            Difficulty::Custom(BoardSize {
+                width: 30,
+                height: 16,
+                mines: 120
+            })
+        );
+        assert_eq!(options.seed, Some(7));
+        assert_eq!(
+            parse_str("--preset intermediate").unwrap().difficulty,
+            Difficulty::Intermediate
+        );
+    }
+
+    #[test]
+    fn test_parse_variants() {
+        let options = parse_str("--two-colour --lives 3 --no-question-marks").unwrap();
+        assert_eq!(options.variant, Variant::TwoColour);
+        assert_eq!(options.lives, Some(3));
+        assert!(!options.question_marks);
+        assert_eq!(
+            parse_str("--render reader").unwrap().render,
+            Some(Style::Reader)
+        );
+        assert_eq!(parse_str("daily").unwrap().game, Game::Daily);
+        assert_eq!(
+            parse_str("--infinite --seed 3").unwrap().game,
+            Game::Infinite
+        );
+    }
+
+    #[test]
+    fn test_parse_batch() {
+        let options = parse_str("--batch 100 --preset expert --format json --solver safe").unwrap();
+        assert_eq!(options.game, Game::Batch { games: 100 });
+        assert_eq!(options.difficulty, Difficulty::Expert);
+        assert_eq!(options.format, Format::Json);
+        assert_eq!(options.solver, Solver::Safe);
+        assert_eq!(
+            parse_str("--format json"),
+            Err(ArgsError::Requires("--format", "--batch"))
+        );
+        assert_eq!(
+            parse_str("--batch 2 --solver smart"),
+            Err(ArgsError::BadValue {
+                option: "--solver",
+                value: "smart".into()
+            })
+        );
+        assert_eq!(
+            parse_str("--edit tutorial.txt --width 5").unwrap().game,
+            Game::Edit {
+                path: "tutorial.txt".into()
+            }
+        );
+        // too small for the preset's mines, which the editor doesn't place
+        let options = parse_str("--edit tiny.txt --width 3 --height 3").unwrap();
+        assert_eq!(options.difficulty.size().width, 3);
+        assert_eq!(options.difficulty.size().height, 3);
+        assert_eq!(
+            parse_str("--analyze lost.txt --render ascii").unwrap().game,
+            Game::Analyse {
+                path: "lost.txt".into()
+            }
+        );
+        let options = parse_str("--replay game.json --gif game.gif --cast game.cast").unwrap();
+        assert_eq!(
+            options.game,
+            Game::Replay {
+                path: "game.json".into()
+            }
+        );
+        assert_eq!(options.gif, Some("game.gif".into()));
+        assert_eq!(options.cast, Some("game.cast".into()));
+        assert_eq!(
+            parse_str("--replay game.json"),
+            Err(ArgsError::Requires("--replay", "--gif or --cast"))
+        );
+        assert_eq!(
+            parse_str("--gif game.gif"),
+            Err(ArgsError::Requires("--gif", "--replay"))
+        );
+        assert_eq!(
+            parse_str("--svg lost.txt --analyse lost.txt"),
+            Err(ArgsError::Conflict("--svg", "--analyse"))
+        );
+        assert_eq!(
+            parse_str("--batch 2 --daily"),
+            Err(ArgsError::Conflict("--batch", "--daily"))
+        );
+        assert_eq!(
+            parse_str("--batch 2 --negative"),
+            Err(ArgsError::Conflict("--batch", "--negative"))
+        );
+        assert_eq!(
+            parse_str("--two-colour --batch 2"),
+            Err(ArgsError::Conflict("--batch", "--two-colour"))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_str("--width"),
+            Err(ArgsError::MissingValue("--width"))
+        );
+        assert_eq!(
+            parse_str("--mines lots"),
+            Err(ArgsError::BadValue {
+                option: "--mines",
+                value: "lots".into()
+            })
+        );
+        assert_eq!(
+            parse_str("--mines 81"),
+            Err(ArgsError::Difficulty(DifficultyError::TooManyMines {
+                mines: 81,
+                max: 80
+            }))
+        );
+        assert_eq!(
+            parse_str("--width 101"),
+            Err(ArgsError::Difficulty(DifficultyError::BadSide(101)))
+        );
+        assert_eq!(
+            parse_str("--preset hard"),
+            Err(ArgsError::Difficulty(DifficultyError::UnknownPreset(
+                "hard".into()
+            )))
+        );
+        assert_eq!(
+            parse_str("--seed 1 --daily"),
+            Err(ArgsError::Conflict("--daily", "--seed"))
+        );
+        assert_eq!(
+            parse_str("--negative=yes"),
+            Err(ArgsError::BadValue {
+                option: "--negative",
+                value: "--negative=yes".into()
+            })
+        );
+        assert_eq!(
+            parse_str("--big"),
+            Err(ArgsError::UnknownOption("--big".into()))
+        );
+    }
+}
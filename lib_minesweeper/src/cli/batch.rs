@@ -0,0 +1,151 @@
+//! Lets the solver play many boards without showing them, writing a line
+//! for each game to feed spreadsheets and dashboards.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::time::Instant;
+
+use serde_derive::Serialize;
+
+use lib_minesweeper::analysis::analyse;
+use lib_minesweeper::solver::{play, play_safely};
+use lib_minesweeper::BoardState;
+
+use crate::cli::args::{Format, Options, Solver};
+use crate::new_board;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Won,
+    Failed,
+    /// The safe solver had to guess.
+    Stuck,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Won => write!(f, "won"),
+            Outcome::Failed => write!(f, "failed"),
+            Outcome::Stuck => write!(f, "stuck"),
+        }
+    }
+}
+
+/// How the solver did on one board.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct GameResult {
+    pub seed: u64,
+    pub outcome: Outcome,
+    pub moves: usize,
+    pub guesses: usize,
+    #[serde(rename = "3bv")]
+    pub three_bv: usize,
+    /// How long the solver took.
+    pub seconds: f64,
+}
+
+const CSV_HEADER: &str = "seed,outcome,moves,guesses,3bv,seconds";
+
+/// Plays the board `options` ask for with `seed`.
+pub fn play_one(options: &Options, seed: u64) -> GameResult {
+    let board = new_board(options, seed);
+    let started_at = Instant::now();
+    let played = match options.solver {
+        Solver::Guess => play(&board),
+        Solver::Safe => play_safely(&board),
+    };
+    let seconds = started_at.elapsed().as_secs_f64();
+    GameResult {
+        seed,
+        outcome: match played.board.state {
+            BoardState::Won => Outcome::Won,
+            BoardState::Failed => Outcome::Failed,
+            _ => Outcome::Stuck,
+        },
+        moves: played.moves,
+        guesses: played.guesses,
+        three_bv: analyse(&board).three_bv,
+        seconds,
+    }
+}
+
+/// Plays `games` boards with the seeds counting up from `seed`, so any of
+/// them can be played again with its own seed.
+pub fn run(options: &Options, games: usize, seed: u64, out: &mut impl Write) -> io::Result<()> {
+    if options.format == Format::Csv {
+        writeln!(out, "{}", CSV_HEADER)?;
+    }
+    for i in 0..games {
+        let result = play_one(options, seed.wrapping_add(i as u64));
+        match options.format {
+            Format::Csv => writeln!(
+                out,
+                "{},{},{},{},{},{:.6}",
+                result.seed,
+                result.outcome,
+                result.moves,
+                result.guesses,
+                result.three_bv,
+                result.seconds
+            )?,
+            Format::Json => writeln!(
+                out,
+                "{}",
+                serde_json::to_string(&result).expect("results serialize")
+            )?,
+        }
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::args::parse;
+    use pretty_assertions::assert_eq;
+
+    fn options(args: &str) -> Options {
+        parse(args.split_whitespace().map(String::from)).unwrap()
+    }
+
+    #[test]
+    fn test_run_csv() {
+        let mut out = vec![];
+        run(&options("--batch 3"), 3, 10, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], CSV_HEADER);
+        assert!(lines[1].starts_with("10,"));
+        assert!(lines[3].starts_with("12,"));
+        assert_eq!(lines[1].split(',').count(), 6);
+    }
+
+    #[test]
+    fn test_run_json() {
+        let options = options("--batch 2 --format json --solver safe");
+        let mut out = vec![];
+        run(&options, 2, 7, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let first: serde_json::Value = serde_json::from_str(out.lines().next().unwrap()).unwrap();
+        let result = play_one(&options, 7);
+        assert_eq!(first["seed"], 7);
+        assert_eq!(first["moves"], result.moves);
+        assert_eq!(first["3bv"], result.three_bv);
+        assert_eq!(first["outcome"], result.outcome.to_string());
+        assert_eq!(out.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_safe_solver_never_fails() {
+        let options = options("--batch 20 --solver safe");
+        for seed in 0..20 {
+            let result = play_one(&options, seed);
+            // only the opening click is a guess, and it may be unlucky
+            assert!(result.guesses <= 1);
+            assert!(result.outcome != Outcome::Failed || result.moves == 1);
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! What can be typed at the prompt. A line can hold several commands, one
+//! after the other, like `oA1 fB2 hint`.
+
+use std::fmt;
+use std::iter::Peekable;
+
+use lib_minesweeper::Point;
+
+use crate::cli::coords::parse_point;
+
+pub const HELP: &str = "Commands, several to a line if you like:
+  open B3, o B3, oB3   open a cell, or o 2,3 with numbers for the column
+  flag B3, f B3, fB3   flag a cell, again to take the flag off
+  chord B3, c B3, cB3  open around a number with all its flags
+  undo, u              take the last move back
+  hint, ?              say what the robot would do
+  robot, r             let the robot make one move
+  auto, a              let the robot play until it has to guess
+  save FILE            write the board to FILE
+  load FILE            play the board in FILE
+  export FILE          draw the board as an SVG image in FILE
+  record FILE          write the game so far to FILE, to watch with --replay
+  new, n               start another game
+  help, h              show this
+  quit, q              stop playing";
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Operation {
+    Open { point: Point },
+    Flag { point: Point },
+    Chord { point: Point },
+    Undo,
+    Hint,
+    Robot,
+    Auto,
+    Save { path: String },
+    Load { path: String },
+    Export { path: String },
+    Record { path: String },
+    New,
+    Help,
+    Quit,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum CommandError {
+    Unknown(String),
+    /// A command was given without what it works on.
+    MissingArgument(&'static str),
+    BadPoint(String),
+    OffBoard(String),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CommandError::Unknown(command) => {
+                write!(f, "unknown command '{}', type help to see them", command)
+            }
+            CommandError::MissingArgument(command) => match *command {
+                "save" | "load" | "export" | "record" => write!(f, "{} needs a file", command),
+                _ => write!(f, "{} needs a cell, like {} B3", command, command),
+            },
+            CommandError::BadPoint(point) => {
+                write!(f, "'{}' isn't a cell, try something like B3", point)
+            }
+            CommandError::OffBoard(point) => write!(f, "{} is off the board", point),
+        }
+    }
+}
+
+/// Reads the commands on `line`, for a `width` by `height` board. Nothing is
+/// returned unless every command makes sense.
+pub fn parse_commands(
+    line: &str,
+    width: usize,
+    height: usize,
+) -> Result<Vec<Operation>, CommandError> {
+    let mut words = line
+        .split(|c: char| c.is_whitespace() || c == ';')
+        .filter(|word| !word.is_empty())
+        .peekable();
+    let mut operations = vec![];
+    while let Some(word) = words.next() {
+        let mut point = |command| next_point(&mut words, command, width, height);
+        let operation = match word.to_ascii_lowercase().as_str() {
+            "open" | "o" => Operation::Open {
+                point: point("open")?,
+            },
+            "flag" | "f" => Operation::Flag {
+                point: point("flag")?,
+            },
+            "chord" | "c" => Operation::Chord {
+                point: point("chord")?,
+            },
+            "undo" | "u" => Operation::Undo,
+            "hint" | "?" => Operation::Hint,
+            "robot" | "r" => Operation::Robot,
+            "auto" | "a" => Operation::Auto,
+            "save" => Operation::Save {
+                path: next_file(&mut words, "save")?,
+            },
+            "load" => Operation::Load {
+                path: next_file(&mut words, "load")?,
+            },
+            "export" => Operation::Export {
+                path: next_file(&mut words, "export")?,
+            },
+            "record" => Operation::Record {
+                path: next_file(&mut words, "record")?,
+            },
+            "new" | "n" => Operation::New,
+            "help" | "h" => Operation::Help,
+            "quit" | "q" | "exit" => Operation::Quit,
+            // the short forms go without a space, like oB3
+            other => {
+                let op = other.chars().next().unwrap();
+                let point = || on_board(&word[op.len_utf8()..], width, height);
+                match op {
+                    'o' => Operation::Open { point: point()? },
+                    'f' => Operation::Flag { point: point()? },
+                    'c' => Operation::Chord { point: point()? },
+                    _ => return Err(CommandError::Unknown(word.to_string())),
+                }
+            }
+        };
+        operations.push(operation);
+    }
+    Ok(operations)
+}
+
+/// The cell `command` is for, taken from the next of `words`, or the next
+/// two if they're numbers split by a space.
+pub fn next_point<'a, I: Iterator<Item = &'a str>>(
+    words: &mut Peekable<I>,
+    command: &'static str,
+    width: usize,
+    height: usize,
+) -> Result<Point, CommandError> {
+    let mut text = words
+        .next()
+        .ok_or(CommandError::MissingArgument(command))?
+        .to_string();
+    let is_number = |word: &str| word.chars().all(|c| c.is_ascii_digit());
+    if is_number(&text) {
+        if let Some(row) = words.next_if(|word| is_number(word)) {
+            text = format!("{} {}", text, row);
+        }
+    }
+    on_board(&text, width, height)
+}
+
+/// The file `command` is for, which is the next of `words`.
+pub fn next_file<'a>(
+    words: &mut impl Iterator<Item = &'a str>,
+    command: &'static str,
+) -> Result<String, CommandError> {
+    words
+        .next()
+        .map(String::from)
+        .ok_or(CommandError::MissingArgument(command))
+}
+
+fn on_board(text: &str, width: usize, height: usize) -> Result<Point, CommandError> {
+    let (x, y) = parse_point(text).ok_or_else(|| CommandError::BadPoint(text.to_string()))?;
+    if x >= width || y >= height {
+        return Err(CommandError::OffBoard(text.to_string()));
+    }
+    Ok(Point::new(x, y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            parse_commands("oA1 flag b2; c 3 1 undo", 5, 5),
+            Ok(vec![
+                Operation::Open {
+                    point: Point::new(0, 0)
+                },
+                Operation::Flag {
+                    point: Point::new(1, 1)
+                },
+                Operation::Chord {
+                    point: Point::new(2, 0)
+                },
+                Operation::Undo,
+            ])
+        );
+        assert_eq!(
+            parse_commands("save my board.txt ? q", 5, 5),
+            Err(CommandError::Unknown("board.txt".into()))
+        );
+        assert_eq!(
+            parse_commands("SAVE game.txt export game.svg auto r", 5, 5),
+            Ok(vec![
+                Operation::Save {
+                    path: "game.txt".into()
+                },
+                Operation::Export {
+                    path: "game.svg".into()
+                },
+                Operation::Auto,
+                Operation::Robot,
+            ])
+        );
+        assert_eq!(parse_commands("  ", 5, 5), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_parse_commands_errors() {
+        assert_eq!(
+            parse_commands("o", 5, 5),
+            Err(CommandError::MissingArgument("open"))
+        );
+        assert_eq!(
+            parse_commands("load", 5, 5),
+            Err(CommandError::MissingArgument("load"))
+        );
+        assert_eq!(
+            parse_commands("record", 5, 5),
+            Err(CommandError::MissingArgument("record"))
+        );
+        assert_eq!(
+            parse_commands("oF1", 5, 5),
+            Err(CommandError::OffBoard("F1".into()))
+        );
+        assert_eq!(
+            parse_commands("open 6 1", 5, 5),
+            Err(CommandError::OffBoard("6 1".into()))
+        );
+        assert_eq!(
+            parse_commands("fZZ", 5, 5),
+            Err(CommandError::BadPoint("ZZ".into()))
+        );
+        assert_eq!(
+            parse_commands("dance", 5, 5),
+            Err(CommandError::Unknown("dance".into()))
+        );
+    }
+}
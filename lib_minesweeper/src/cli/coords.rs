@@ -0,0 +1,113 @@
+//! Coordinates as players type them and as the axes are labelled: columns
+//! are letters like in a spreadsheet (A to Z, then AA, AB, ...) and rows are
+//! numbers starting at 1. Columns can also be given as numbers, so `B3`,
+//! `b3`, `2,3` and `2 3` all mean the same cell.
+
+use lib_minesweeper::Point;
+
+/// The label of column `x`, counting from 0.
+pub fn column_label(x: usize) -> String {
+    let mut label = vec![];
+    let mut n = x + 1;
+    while n > 0 {
+        n -= 1;
+        label.push(b'A' + (n % 26) as u8);
+        n /= 26;
+    }
+    label.iter().rev().map(|c| *c as char).collect()
+}
+
+/// The label of row `y`, counting from 0.
+pub fn row_label(y: usize) -> String {
+    (y + 1).to_string()
+}
+
+/// How a cell on the board is written, like `B3`.
+pub fn cell_label(p: &Point) -> String {
+    format!("{}{}", column_label(p.x as usize), row_label(p.y as usize))
+}
+
+/// The column labelled `letters`, in either case.
+fn parse_column(letters: &str) -> Option<usize> {
+    if letters.is_empty() {
+        return None;
+    }
+    letters
+        .chars()
+        .try_fold(0usize, |n, c| {
+            if !c.is_ascii_alphabetic() {
+                return None;
+            }
+            let digit = (c.to_ascii_uppercase() as u8 - b'A') as usize + 1;
+            n.checked_mul(26)?.checked_add(digit)
+        })
+        .map(|n| n - 1)
+}
+
+/// A label counting from 1 as a position counting from 0.
+fn parse_number(digits: &str) -> Option<usize> {
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse::<usize>().ok()?.checked_sub(1)
+}
+
+/// The `(x, y)` position of a cell written as `B3` or `2,3`, whichever the
+/// player prefers. Says nothing about whether it's on the board.
+pub fn parse_point(s: &str) -> Option<(usize, usize)> {
+    let s = s.trim();
+    let letters = s
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(s.len());
+    if letters > 0 {
+        let (column, row) = s.split_at(letters);
+        return Some((parse_column(column)?, parse_number(row.trim())?));
+    }
+    let mut parts = s
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty());
+    let x = parse_number(parts.next()?)?;
+    let y = parse_number(parts.next()?)?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((x, y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_column_label() {
+        let labels: Vec<String> = [0, 1, 25, 26, 27, 51, 52, 701, 702]
+            .iter()
+            .map(|x| column_label(*x))
+            .collect();
+        assert_eq!(
+            labels,
+            vec!["A", "B", "Z", "AA", "AB", "AZ", "BA", "ZZ", "AAA"]
+        );
+        for x in 0..1000 {
+            assert_eq!(parse_column(&column_label(x)), Some(x));
+        }
+        assert_eq!(cell_label(&Point::new(27, 39)), "AB40");
+    }
+
+    #[test]
+    fn test_parse_point() {
+        assert_eq!(parse_point("A1"), Some((0, 0)));
+        assert_eq!(parse_point("ab40"), Some((27, 39)));
+        assert_eq!(parse_point("C 12"), Some((2, 11)));
+        assert_eq!(parse_point("2,3"), Some((1, 2)));
+        assert_eq!(parse_point(" 40 100 "), Some((39, 99)));
+    }
+
+    #[test]
+    fn test_parse_bad_point() {
+        for bad in ["", "A", "12", "A0", "0,1", "1,2,3", "A1B", "A-1", "1;2"] {
+            assert_eq!(parse_point(bad), None, "{:?} shouldn't parse", bad);
+        }
+    }
+}
@@ -0,0 +1,302 @@
+//! Puts positions together by hand, for tutorials and regression tests:
+//! place mines, open and flag cells, and save the board in the text format.
+//! The numbers are worked out as the mines move.
+
+use std::fmt;
+
+use lib_minesweeper::MapElement::{self, Mine, Number};
+use lib_minesweeper::MapElementCellState::{self, Closed, Flagged, Open};
+use lib_minesweeper::{create_position, Board, MineKind, Point};
+
+use crate::cli::commands::{next_file, next_point, CommandError};
+
+pub const HELP: &str = "Commands, several to a line if you like:
+  mine B3              put a mine on a cell, or take it off
+  negative B3          the same with a negative mine
+  secondary B3         the same with a second colour mine
+  open B3              open a cell, or close it again
+  flag B3              flag a cell, or take the flag off
+  clear                take every mine off and close every cell
+  save                 write the board to the file being edited
+  export FILE          draw the board as an SVG image in FILE
+  help                 show this
+  quit                 stop editing";
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Edit {
+    Mine { point: Point, kind: MineKind },
+    Open { point: Point },
+    Flag { point: Point },
+    Clear,
+    Save,
+    Export { path: String },
+    Help,
+    Quit,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum EditError {
+    /// Boards have negative or second colour mines, not both.
+    MixedKinds,
+    OpenMine(Point),
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EditError::MixedKinds => write!(
+                f,
+                "a board can have negative or second colour mines, but not both"
+            ),
+            EditError::OpenMine(p) => write!(
+                f,
+                "{} is a mine, take it off before opening it",
+                crate::cli::coords::cell_label(p)
+            ),
+        }
+    }
+}
+
+/// Reads the edits on `line`, for a `width` by `height` board.
+pub fn parse_edits(line: &str, width: usize, height: usize) -> Result<Vec<Edit>, CommandError> {
+    let mut words = line
+        .split(|c: char| c.is_whitespace() || c == ';')
+        .filter(|word| !word.is_empty())
+        .peekable();
+    let mut edits = vec![];
+    while let Some(word) = words.next() {
+        let mut point = |command| next_point(&mut words, command, width, height);
+        let edit = match word.to_ascii_lowercase().as_str() {
+            "mine" | "m" => Edit::Mine {
+                point: point("mine")?,
+                kind: MineKind::Positive,
+            },
+            "negative" => Edit::Mine {
+                point: point("negative")?,
+                kind: MineKind::Negative,
+            },
+            "secondary" => Edit::Mine {
+                point: point("secondary")?,
+                kind: MineKind::Secondary,
+            },
+            "open" | "o" => Edit::Open {
+                point: point("open")?,
+            },
+            "flag" | "f" => Edit::Flag {
+                point: point("flag")?,
+            },
+            "clear" => Edit::Clear,
+            "save" => Edit::Save,
+            "export" => Edit::Export {
+                path: next_file(&mut words, "export")?,
+            },
+            "help" | "h" => Edit::Help,
+            "quit" | "q" | "exit" => Edit::Quit,
+            _ => return Err(CommandError::Unknown(word.to_string())),
+        };
+        edits.push(edit);
+    }
+    Ok(edits)
+}
+
+/// A board being edited. Only where the mines are and the state of each cell
+/// are kept, the rest is worked out when it's needed.
+pub struct Editor {
+    map: Vec<Vec<MapElement>>,
+    pub lives: Option<u32>,
+    pub question_marks: bool,
+    pub fair_guesses: bool,
+}
+
+impl Editor {
+    /// An empty `width` by `height` board.
+    pub fn new(width: usize, height: usize) -> Self {
+        let closed = Number {
+            state: Closed,
+            count: 0,
+            secondary_count: 0,
+        };
+        Editor {
+            map: vec![vec![closed; width]; height],
+            lives: None,
+            question_marks: false,
+            fair_guesses: false,
+        }
+    }
+
+    /// Carries on editing `board`.
+    pub fn of(board: &Board) -> Self {
+        let map = (0..board.height)
+            .map(|y| {
+                (0..board.width)
+                    .map(|x| board.at(&Point::new(x, y)).unwrap().clone())
+                    .collect()
+            })
+            .collect();
+        Editor {
+            map,
+            lives: board.lives,
+            question_marks: board.question_marks,
+            fair_guesses: board.fair_guesses,
+        }
+    }
+
+    /// The board as it stands.
+    pub fn board(&self) -> Board {
+        let board = create_position(self.map.clone())
+            .with_question_marks(self.question_marks)
+            .with_fair_guesses(self.fair_guesses);
+        match self.lives {
+            Some(lives) => board.with_lives(lives),
+            None => board,
+        }
+    }
+
+    fn at(&mut self, p: &Point) -> &mut MapElement {
+        &mut self.map[p.y as usize][p.x as usize]
+    }
+
+    /// Makes a change to a cell, or to all of them for `clear`. Saving,
+    /// exporting, help and quitting are up to whoever is running the editor.
+    pub fn apply(&mut self, edit: &Edit) -> Result<(), EditError> {
+        match edit {
+            Edit::Mine { point, kind } => {
+                let other = match kind {
+                    MineKind::Positive => None,
+                    MineKind::Negative => Some(MineKind::Secondary),
+                    MineKind::Secondary => Some(MineKind::Negative),
+                };
+                let mixed = self.map.iter().flatten().any(
+                    |element| matches!(element, Mine { kind, .. } if Some(kind) == other.as_ref()),
+                );
+                if mixed {
+                    return Err(EditError::MixedKinds);
+                }
+                let element = self.at(point);
+                *element = match &*element {
+                    Mine { kind: old, state } if old == kind => Number {
+                        state: state.clone(),
+                        count: 0,
+                        secondary_count: 0,
+                    },
+                    // a mine can't be open without the game being over
+                    element => Mine {
+                        state: match element.state() {
+                            Open => Closed,
+                            state => state.clone(),
+                        },
+                        kind: kind.clone(),
+                    },
+                };
+            }
+            Edit::Open { point } => {
+                let state = match self.at(point) {
+                    Mine { .. } => return Err(EditError::OpenMine(*point)),
+                    Number { state: Open, .. } => Closed,
+                    Number { .. } => Open,
+                };
+                set_state(self.at(point), state);
+            }
+            Edit::Flag { point } => {
+                let state = match self.at(point) {
+                    Mine {
+                        state: Flagged(_), ..
+                    }
+                    | Number {
+                        state: Flagged(_), ..
+                    } => Closed,
+                    Mine { kind, .. } => Flagged(kind.clone()),
+                    Number { .. } => Flagged(MineKind::Positive),
+                };
+                set_state(self.at(point), state);
+            }
+            Edit::Clear => {
+                for element in self.map.iter_mut().flatten() {
+                    *element = Number {
+                        state: Closed,
+                        count: 0,
+                        secondary_count: 0,
+                    };
+                }
+            }
+            Edit::Save | Edit::Export { .. } | Edit::Help | Edit::Quit => (),
+        }
+        Ok(())
+    }
+}
+
+fn set_state(element: &mut MapElement, new_state: MapElementCellState) {
+    match element {
+        Mine { state, .. } | Number { state, .. } => *state = new_state,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_minesweeper::text::to_text;
+    use lib_minesweeper::BoardState;
+    use pretty_assertions::assert_eq;
+
+    fn edit(editor: &mut Editor, line: &str) -> Result<(), EditError> {
+        for edit in parse_edits(line, 4, 2).unwrap() {
+            editor.apply(&edit)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_editor() {
+        let mut editor = Editor::new(4, 2);
+        editor.lives = Some(2);
+        edit(&mut editor, "mine A1 mine D2 open B1 open 3 1 flag A1").unwrap();
+        let board = editor.board();
+        assert_eq!(
+            to_text(&board),
+            "state playing
+lives 2
+
+X...
+...X
+
+FOOC
+CCCC
+"
+        );
+        assert!(matches!(
+            board.at(&Point::new(2, 0)),
+            Some(Number { count: 1, .. })
+        ));
+        // taking the mine off leaves the flag where it was
+        edit(&mut editor, "mine D2 mine A1").unwrap();
+        assert_eq!(editor.board().mines, 0);
+        assert_eq!(
+            editor.board().at(&Point::new(0, 0)).unwrap().state(),
+            &Flagged(MineKind::Positive)
+        );
+        edit(&mut editor, "clear").unwrap();
+        assert_eq!(editor.board().state, BoardState::Ready);
+        assert_eq!(editor.board().lives, Some(2));
+    }
+
+    #[test]
+    fn test_editor_errors() {
+        let mut editor = Editor::new(4, 2);
+        assert_eq!(
+            edit(&mut editor, "mine A1 open A1"),
+            Err(EditError::OpenMine(Point::new(0, 0)))
+        );
+        assert_eq!(
+            edit(&mut editor, "negative B1 secondary C1"),
+            Err(EditError::MixedKinds)
+        );
+        assert_eq!(
+            parse_edits("mine", 4, 2),
+            Err(CommandError::MissingArgument("mine"))
+        );
+        assert_eq!(
+            parse_edits("dig A1", 4, 2),
+            Err(CommandError::Unknown("dig".into()))
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! The chance of each closed cell of a saved position being a mine, to see
+//! what was safe, what was a guess and whether a loss could have been
+//! avoided.
+
+use colored::Colorize;
+
+use lib_minesweeper::solver::probabilities;
+use lib_minesweeper::MapElement::{self, Mine, Number};
+use lib_minesweeper::MapElementCellState::{Closed, Exploded, Flagged, Revealed, WronglyFlagged};
+use lib_minesweeper::{create_position, Board, BoardState, MineKind, Point};
+
+use crate::cli::coords::cell_label;
+use crate::cli::render::{self, Style};
+
+/// Cells are two characters wide, to fit a percentage.
+pub const CELL_WIDTH: usize = 2;
+
+/// What the solver makes of a position.
+pub struct Heatmap {
+    /// The position as it was before any mine went off.
+    pub board: Board,
+    /// The chance of each closed, questioned or flagged cell being a mine,
+    /// in board order.
+    pub probabilities: Vec<(Point, f64)>,
+    /// The cells that went off, if the game was lost.
+    pub exploded: Vec<Point>,
+}
+
+impl Heatmap {
+    /// Works out `board`, or `None` if it isn't a classic board or its
+    /// numbers don't add up.
+    pub fn of(board: &Board) -> Option<Heatmap> {
+        let exploded: Vec<Point> = board
+            .points()
+            .filter(|p| board.at(p).unwrap().state() == &Exploded)
+            .collect();
+        let board = if board.state == BoardState::Failed {
+            rewind(board)
+        } else {
+            board.clone()
+        };
+        Some(Heatmap {
+            probabilities: probabilities(&board)?,
+            board,
+            exploded,
+        })
+    }
+
+    pub fn probability(&self, p: &Point) -> Option<f64> {
+        self.probabilities
+            .iter()
+            .find(|(q, _)| q == p)
+            .map(|(_, probability)| *probability)
+    }
+
+    /// What the heatmap shows for `p`: its chance of being a mine if it
+    /// isn't open, or the cell itself if it is.
+    pub fn cell(&self, style: Style, p: &Point) -> String {
+        let element = self.board.at(p).unwrap();
+        let Some(probability) = self.probability(p) else {
+            let cell = render::cell(
+                style,
+                element,
+                self.board.has_surrounding_mines(p),
+                &self.board.variant,
+                false,
+            );
+            // padded outside any colour, which doesn't take up room
+            let width = render::cell_width(style, &self.board.variant);
+            return match style {
+                Style::Reader => cell,
+                _ => cell + &" ".repeat(CELL_WIDTH.saturating_sub(width)),
+            };
+        };
+        let text = match percent(probability) {
+            0 => "S".to_string(),
+            100 => "M".to_string(),
+            percent => percent.to_string(),
+        };
+        match style {
+            Style::Reader => match percent(probability) {
+                0 => "safe".to_string(),
+                100 => "mine".to_string(),
+                percent => format!("{}%", percent),
+            },
+            Style::Colour => {
+                let text = format!("{:>1$}", text, CELL_WIDTH).black();
+                match probability {
+                    p if p < 0.2 => text.on_bright_green(),
+                    p if p < 0.4 => text.on_bright_yellow(),
+                    p if p < 0.6 => text.on_yellow(),
+                    p if p < 0.8 => text.on_bright_red(),
+                    _ => text.on_red(),
+                }
+                .to_string()
+            }
+            Style::Unicode | Style::Ascii => format!("{:>1$}", text, CELL_WIDTH),
+        }
+    }
+
+    /// The certain cells, the best guess and, for lost games, how likely
+    /// the cells that went off were to be mines, a sentence each.
+    pub fn summary(&self) -> Vec<String> {
+        let list = |probability: f64| -> Vec<String> {
+            self.probabilities
+                .iter()
+                .filter(|(p, q)| *q == probability && !is_flagged(self.board.at(p).unwrap()))
+                .map(|(p, _)| cell_label(p))
+                .collect()
+        };
+        let mut summary = vec![];
+        let safe = list(0.0);
+        if !safe.is_empty() {
+            summary.push(format!("Safe: {}.", safe.join(", ")));
+        }
+        let mines = list(1.0);
+        if !mines.is_empty() {
+            summary.push(format!("Mines without flags: {}.", mines.join(", ")));
+        }
+        let best = self
+            .probabilities
+            .iter()
+            .filter(|(p, q)| *q > 0.0 && *q < 1.0 && !is_flagged(self.board.at(p).unwrap()))
+            .fold(None, |best: Option<&(Point, f64)>, candidate| match best {
+                Some(best) if best.1 <= candidate.1 => Some(best),
+                _ => Some(candidate),
+            });
+        if let (true, Some((p, q))) = (safe.is_empty(), best) {
+            summary.push(format!(
+                "Nothing is certain, the best guess is {} at {}%.",
+                cell_label(p),
+                percent(*q)
+            ));
+        }
+        for p in &self.exploded {
+            let probability = self.probability(p).unwrap_or(1.0);
+            summary.push(match (percent(probability), safe.is_empty()) {
+                (100, _) => format!("{} was certain to be a mine.", cell_label(p)),
+                (chance, true) => format!(
+                    "{} had a {}% chance of being a mine, and there was nothing safe to open instead.",
+                    cell_label(p),
+                    chance
+                ),
+                (chance, false) => format!(
+                    "{} had a {}% chance of being a mine, but {} was safe.",
+                    cell_label(p),
+                    chance,
+                    safe[0]
+                ),
+            });
+        }
+        summary
+    }
+}
+
+/// A probability as a whole percentage, where only certain cells make 0 or
+/// 100.
+fn percent(probability: f64) -> u32 {
+    if probability == 0.0 {
+        0
+    } else if probability == 1.0 {
+        100
+    } else {
+        ((probability * 100.0).round() as u32).clamp(1, 99)
+    }
+}
+
+fn is_flagged(element: &MapElement) -> bool {
+    matches!(element.state(), Flagged(_))
+}
+
+/// The lost game on `board` as it was before the mines went off.
+fn rewind(board: &Board) -> Board {
+    let map = (0..board.height)
+        .map(|y| {
+            (0..board.width)
+                .map(|x| match board.at(&Point::new(x, y)).unwrap() {
+                    Mine {
+                        state: Exploded | Revealed,
+                        kind,
+                    } => Mine {
+                        state: Closed,
+                        kind: kind.clone(),
+                    },
+                    Mine {
+                        state: WronglyFlagged,
+                        kind,
+                    } => Mine {
+                        state: Flagged(kind.clone()),
+                        kind: kind.clone(),
+                    },
+                    Number {
+                        state: WronglyFlagged,
+                        count,
+                        secondary_count,
+                    } => Number {
+                        state: Flagged(MineKind::Positive),
+                        count: *count,
+                        secondary_count: *secondary_count,
+                    },
+                    element => element.clone(),
+                })
+                .collect()
+        })
+        .collect();
+    create_position(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_minesweeper::text::from_text;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_heatmap() {
+        // the 1 in the corner leaves A1 and B1 as a 50/50, and C3 is clear
+        let board = from_text(
+            "X...
+             ....
+             ....
+
+             CCCC
+             OOCC
+             OOCC",
+        )
+        .unwrap();
+        let heatmap = Heatmap::of(&board).unwrap();
+        assert_eq!(heatmap.cell(Style::Ascii, &Point::new(0, 1)), "1 ");
+        assert_eq!(heatmap.cell(Style::Ascii, &Point::new(0, 0)), "50");
+        assert_eq!(heatmap.cell(Style::Ascii, &Point::new(2, 2)), " S");
+        assert_eq!(heatmap.cell(Style::Reader, &Point::new(2, 0)), "safe");
+        assert_eq!(heatmap.summary(), vec!["Safe: C1, D1, C2, D2, C3, D3."]);
+    }
+
+    #[test]
+    fn test_heatmap_of_a_loss() {
+        let board = from_text(
+            "state failed
+
+             X...
+             ....
+             ....
+
+             ECCC
+             OOCC
+             OOCC",
+        )
+        .unwrap();
+        let heatmap = Heatmap::of(&board).unwrap();
+        assert_eq!(heatmap.exploded, vec![Point::new(0, 0)]);
+        assert_eq!(heatmap.board.state, BoardState::Playing);
+        assert_eq!(
+            heatmap.summary()[1],
+            "A1 had a 50% chance of being a mine, but C1 was safe."
+        );
+    }
+}
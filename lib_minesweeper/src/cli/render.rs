@@ -0,0 +1,319 @@
+//! The ways a cell can be drawn: in colour, in plain Unicode or ASCII for
+//! terminals and logs without colour, or described in words for screen
+//! readers.
+
+use colored::Colorize;
+
+use lib_minesweeper::MapElement::{self, Mine, Number};
+use lib_minesweeper::MapElementCellState::{
+    Closed, Exploded, Flagged, Open, Questioned, Revealed, WronglyFlagged,
+};
+use lib_minesweeper::{MineKind, Point, Variant};
+
+use crate::cli::coords::cell_label;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Style {
+    Colour,
+    /// Symbols without colour.
+    Unicode,
+    Ascii,
+    /// Rows read out in words rather than drawn.
+    Reader,
+}
+
+impl Style {
+    /// Whether the style draws a grid, rather than describing it.
+    pub fn is_grid(&self) -> bool {
+        *self != Style::Reader
+    }
+}
+
+/// How many characters each cell takes, so every one on a board lines up.
+pub fn cell_width(style: Style, variant: &Variant) -> usize {
+    match (style, variant) {
+        // two-colour numbers need a column for each count
+        (Style::Colour, Variant::TwoColour) => 2,
+        // colour shows negative counts on a different background
+        (Style::Colour, _) | (_, Variant::Classic) => 1,
+        // without colour they need their signs, and two counts a separator
+        (_, Variant::NegativeMines) => 2,
+        (_, Variant::TwoColour) => 3,
+    }
+}
+
+/// What a cell looks like, `cell_width` characters wide, or what it is in
+/// words for screen readers. Finished boards show every number.
+pub fn cell(
+    style: Style,
+    element: &MapElement,
+    has_surrounding_mines: bool,
+    variant: &Variant,
+    is_done: bool,
+) -> String {
+    let cell_width = cell_width(style, variant);
+    match style {
+        Style::Colour => {
+            colorized_cell(element, has_surrounding_mines, variant, is_done, cell_width)
+        }
+        Style::Reader => cell_words(element, has_surrounding_mines, variant, is_done),
+        Style::Unicode | Style::Ascii => {
+            let symbol = plain_cell(
+                style == Style::Ascii,
+                element,
+                has_surrounding_mines,
+                variant,
+                is_done,
+            );
+            // left aligned, under the column labels
+            format!("{:1$}", symbol, cell_width)
+        }
+    }
+}
+
+fn colorized_cell(
+    element: &MapElement,
+    has_surrounding_mines: bool,
+    variant: &Variant,
+    is_done: bool,
+    cell_width: usize,
+) -> String {
+    let blank = " ".repeat(cell_width);
+    match element {
+        Mine { state, .. } => match (state, is_done) {
+            (Exploded, _) => format!("{:1$}", "*", cell_width)
+                .black()
+                .on_red()
+                .to_string(),
+            (_, true) | (Open, _) | (Revealed, _) => blank.on_red().to_string(),
+            (WronglyFlagged, _) => colorized_wrong_flag(cell_width),
+            (Flagged(kind), _) => colorized_flag(kind, &blank),
+            (Closed, _) => blank.on_yellow().to_string(),
+            (Questioned, _) => format!("{:1$}", "?", cell_width)
+                .black()
+                .on_yellow()
+                .to_string(),
+        },
+        Number {
+            state,
+            count,
+            secondary_count,
+        } => match (state, is_done) {
+            (_, true) | (Open, _) if !has_surrounding_mines => blank.on_bright_white().to_string(),
+            (_, true) | (Open, _) if *variant == Variant::TwoColour => format!(
+                "{}{}",
+                format!("{}", count).black().on_bright_cyan(),
+                format!("{}", secondary_count).red().on_bright_cyan()
+            ),
+            // negative counts keep the grid aligned by showing the
+            // magnitude on a different background
+            (_, true) | (Open, _) if *count < 0 => format!("{}", -count)
+                .black()
+                .on_bright_magenta()
+                .to_string(),
+            (_, true) | (Open, _) => format!("{}", count).black().on_bright_cyan().to_string(),
+            (Flagged(kind), _) => colorized_flag(kind, &blank),
+            (Closed, _) => blank.on_yellow().to_string(),
+            (Questioned, _) => format!("{:1$}", "?", cell_width)
+                .black()
+                .on_yellow()
+                .to_string(),
+            (WronglyFlagged, _) => colorized_wrong_flag(cell_width),
+            (Exploded, _) | (Revealed, _) => unreachable!(),
+        },
+    }
+}
+
+fn colorized_wrong_flag(cell_width: usize) -> String {
+    format!("{:1$}", "x", cell_width)
+        .black()
+        .on_bright_green()
+        .to_string()
+}
+
+fn colorized_flag(kind: &MineKind, blank: &str) -> String {
+    match kind {
+        MineKind::Positive => blank.on_bright_green().to_string(),
+        MineKind::Negative => format!("{:1$}", "-", blank.len())
+            .black()
+            .on_bright_green()
+            .to_string(),
+        MineKind::Secondary => blank.on_bright_red().to_string(),
+    }
+}
+
+/// A cell as a symbol, from `ascii` or otherwise from Unicode.
+fn plain_cell(
+    ascii: bool,
+    element: &MapElement,
+    has_surrounding_mines: bool,
+    variant: &Variant,
+    is_done: bool,
+) -> String {
+    let symbol = |ascii_symbol: &str, unicode_symbol: &str| {
+        if ascii {
+            ascii_symbol.to_string()
+        } else {
+            unicode_symbol.to_string()
+        }
+    };
+    let flag = |kind: &MineKind| match kind {
+        MineKind::Positive => symbol("F", "⚑"),
+        MineKind::Negative => symbol("N", "⊖"),
+        MineKind::Secondary => symbol("S", "⚐"),
+    };
+    match element {
+        Mine { state, .. } => match (state, is_done) {
+            (Exploded, _) => symbol("X", "✸"),
+            (_, true) | (Open, _) | (Revealed, _) => symbol("*", "✱"),
+            (WronglyFlagged, _) => symbol("x", "✗"),
+            (Flagged(kind), _) => flag(kind),
+            (Closed, _) => symbol("#", "■"),
+            (Questioned, _) => "?".to_string(),
+        },
+        Number {
+            state,
+            count,
+            secondary_count,
+        } => match (state, is_done) {
+            (_, true) | (Open, _) if !has_surrounding_mines => symbol(".", "·"),
+            (_, true) | (Open, _) if *variant == Variant::TwoColour => {
+                format!("{}/{}", count, secondary_count)
+            }
+            (_, true) | (Open, _) => count.to_string(),
+            (Flagged(kind), _) => flag(kind),
+            (Closed, _) => symbol("#", "■"),
+            (Questioned, _) => "?".to_string(),
+            (WronglyFlagged, _) => symbol("x", "✗"),
+            (Exploded, _) | (Revealed, _) => unreachable!(),
+        },
+    }
+}
+
+/// A cell in words, for screen readers.
+pub fn cell_words(
+    element: &MapElement,
+    has_surrounding_mines: bool,
+    variant: &Variant,
+    is_done: bool,
+) -> String {
+    let flag = |kind: &MineKind| match kind {
+        MineKind::Positive => "flag",
+        MineKind::Negative => "negative flag",
+        MineKind::Secondary => "second colour flag",
+    };
+    match element {
+        Mine { state, .. } => match (state, is_done) {
+            (Exploded, _) => "exploded mine".into(),
+            (_, true) | (Open, _) | (Revealed, _) => "mine".into(),
+            (WronglyFlagged, _) => "wrong flag".into(),
+            (Flagged(kind), _) => flag(kind).into(),
+            (Closed, _) => "closed".into(),
+            (Questioned, _) => "question mark".into(),
+        },
+        Number {
+            state,
+            count,
+            secondary_count,
+        } => match (state, is_done) {
+            (_, true) | (Open, _) if !has_surrounding_mines => "empty".into(),
+            (_, true) | (Open, _) if *variant == Variant::TwoColour => {
+                format!("{} and {} second colour", count, secondary_count)
+            }
+            (_, true) | (Open, _) if *count < 0 => format!("minus {}", -count),
+            (_, true) | (Open, _) => count.to_string(),
+            (Flagged(kind), _) => flag(kind).into(),
+            (Closed, _) => "closed".into(),
+            (Questioned, _) => "question mark".into(),
+            (WronglyFlagged, _) => "wrong flag".into(),
+            (Exploded, _) | (Revealed, _) => unreachable!(),
+        },
+    }
+}
+
+/// Row `y` read out from the words for each of its cells, with runs of the
+/// same cell said once, like `Row 2: A2 to C2 closed, D2 1, E2 empty.`
+pub fn describe_row(y: usize, words: &[String]) -> String {
+    let mut runs: Vec<String> = vec![];
+    let mut start = 0;
+    for x in 1..=words.len() {
+        if x < words.len() && words[x] == words[start] {
+            continue;
+        }
+        let first = cell_label(&Point::new(start, y));
+        runs.push(if x - 1 == start {
+            format!("{} {}", first, words[start])
+        } else {
+            let last = cell_label(&Point::new(x - 1, y));
+            format!("{} to {} {}", first, last, words[start])
+        });
+        start = x;
+    }
+    format!("Row {}: {}.", y + 1, runs.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn number(state: lib_minesweeper::MapElementCellState, count: i32) -> MapElement {
+        Number {
+            state,
+            count,
+            secondary_count: 1,
+        }
+    }
+
+    #[test]
+    fn test_plain_cells() {
+        let cells = [
+            number(Closed, 1),
+            number(Open, 2),
+            number(Open, -1),
+            Mine {
+                state: Flagged(MineKind::Negative),
+                kind: MineKind::Negative,
+            },
+            Mine {
+                state: Exploded,
+                kind: MineKind::Positive,
+            },
+        ];
+        let draw = |style| {
+            cells
+                .iter()
+                .map(|element| cell(style, element, true, &Variant::NegativeMines, false))
+                .collect::<Vec<_>>()
+                .join("")
+        };
+        assert_eq!(draw(Style::Ascii), "# 2 -1N X ");
+        assert_eq!(draw(Style::Unicode), "■ 2 -1⊖ ✸ ");
+        assert_eq!(
+            cell(
+                Style::Ascii,
+                &number(Open, 2),
+                true,
+                &Variant::TwoColour,
+                false
+            ),
+            "2/1"
+        );
+    }
+
+    #[test]
+    fn test_describe_row() {
+        let words: Vec<String> = ["closed", "closed", "closed", "1", "empty"]
+            .iter()
+            .map(|word| word.to_string())
+            .collect();
+        assert_eq!(
+            describe_row(1, &words),
+            "Row 2: A2 to C2 closed, D2 1, E2 empty."
+        );
+        assert_eq!(
+            cell_words(&number(Open, -2), true, &Variant::NegativeMines, false),
+            "minus 2"
+        );
+    }
+}
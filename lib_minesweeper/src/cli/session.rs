@@ -0,0 +1,206 @@
+//! A game in progress, with what's needed to take moves back, time it, record
+//! it and let the robot help, shared by the prompt and the full screen game.
+
+use std::time::Instant;
+
+use lib_minesweeper::replay::Replay;
+use lib_minesweeper::score::Clicks;
+use lib_minesweeper::solver::{next_move, Move};
+use lib_minesweeper::{Action, Board, BoardState};
+
+use crate::cli::coords::cell_label;
+
+/// A game played to the end.
+pub struct Finished {
+    pub board: Board,
+    pub clicks: Clicks,
+    pub seconds: f64,
+    /// Whether the robot helped or moves were taken back.
+    pub assisted: bool,
+}
+
+pub struct Session {
+    pub board: Board,
+    /// The boards before each move, for undo.
+    history: Vec<Board>,
+    pub clicks: Clicks,
+    started_at: Option<Instant>,
+    /// Seconds the game took, frozen when it ends.
+    seconds: Option<f64>,
+    /// Like the robot in the web version, help keeps a game out of the rating.
+    pub assisted: bool,
+    /// The moves so far, for games played from the start. Positions loaded
+    /// part way through can't be played again from their mines alone.
+    pub replay: Option<Replay>,
+}
+
+impl Session {
+    pub fn new(board: Board) -> Self {
+        Session {
+            history: vec![],
+            clicks: Clicks::default(),
+            started_at: None,
+            seconds: None,
+            assisted: false,
+            replay: (board.state == BoardState::Ready).then(|| Replay::new(&board)),
+            board,
+        }
+    }
+
+    pub fn elapsed(&self) -> f64 {
+        self.seconds.unwrap_or_else(|| {
+            self.started_at
+                .map_or(0.0, |started_at| started_at.elapsed().as_secs_f64())
+        })
+    }
+
+    /// Makes a move, saying whether it changed anything.
+    pub fn apply(&mut self, action: &Action) -> bool {
+        if self.board.is_finished() {
+            return false;
+        }
+        self.started_at.get_or_insert_with(Instant::now);
+        let next = self.board.apply(action);
+        self.clicks.record(action, next.is_some());
+        let Some(board) = next else {
+            return false;
+        };
+        self.history.push(std::mem::replace(&mut self.board, board));
+        let millis = (self.elapsed() * 1000.0) as i64;
+        if let Some(replay) = &mut self.replay {
+            replay.record(millis, action.clone());
+        }
+        if self.board.is_finished() {
+            self.seconds = Some(self.elapsed());
+        }
+        true
+    }
+
+    /// Takes the last move back, even the one that ended the game.
+    pub fn undo(&mut self) -> bool {
+        let Some(board) = self.history.pop() else {
+            return false;
+        };
+        self.board = board;
+        if let Some(replay) = &mut self.replay {
+            replay.actions.pop();
+        }
+        self.seconds = None;
+        self.assisted = true;
+        true
+    }
+
+    /// What the robot would do next.
+    pub fn hint(&mut self) -> Option<Move> {
+        let next = next_move(&self.board)?;
+        self.assisted = true;
+        Some(next)
+    }
+
+    /// Lets the robot make one move, guessing if it has to.
+    pub fn robot(&mut self) -> Option<Move> {
+        let next = self.hint()?;
+        self.apply(&next.action());
+        Some(next)
+    }
+
+    /// Lets the robot play until it would have to guess, returning how many
+    /// moves it made.
+    pub fn auto(&mut self) -> usize {
+        let mut moves = 0;
+        while let Some(next) = next_move(&self.board) {
+            if matches!(next, Move::Guess { .. }) || !self.apply(&next.action()) {
+                break;
+            }
+            self.assisted = true;
+            moves += 1;
+        }
+        moves
+    }
+
+    pub fn finished(&self) -> Option<Finished> {
+        if !self.board.is_finished() {
+            return None;
+        }
+        Some(Finished {
+            board: self.board.clone(),
+            clicks: self.clicks.clone(),
+            seconds: self.elapsed(),
+            assisted: self.assisted,
+        })
+    }
+}
+
+/// What the robot thinks of its move, in words.
+pub fn describe(next: &Move) -> String {
+    match next {
+        Move::Safe(p) => format!("{} is safe", cell_label(p)),
+        Move::Mine(p) => format!("{} is a mine", cell_label(p)),
+        Move::Guess { point, probability } => format!(
+            "nothing is certain, {} is the best guess with a {:.0}% chance of a mine",
+            cell_label(point),
+            probability * 100.0
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_minesweeper::{
+        create_board_with_mines, numbers_on_board, BoardState, MapElementCellState, MineKind, Point,
+    };
+    use pretty_assertions::assert_eq;
+
+    /// Mines in two corners, which the robot can find once the other side
+    /// is open.
+    fn session() -> Session {
+        Session::new(numbers_on_board(create_board_with_mines(
+            4,
+            4,
+            &[
+                (Point::new(3, 0), MineKind::Positive),
+                (Point::new(3, 3), MineKind::Positive),
+            ],
+        )))
+    }
+
+    #[test]
+    fn test_undo() {
+        let mut session = session();
+        assert!(!session.undo());
+        assert!(session.apply(&Action::Flag(Point::new(1, 1))));
+        assert!(session.apply(&Action::Open(Point::new(3, 3))));
+        assert_eq!(session.board.state, BoardState::Failed);
+        assert!(session.finished().is_some());
+        assert!(session.undo());
+        assert!(!session.board.is_finished());
+        assert!(session.finished().is_none());
+        assert!(session.assisted);
+        assert!(session.undo());
+        assert_eq!(
+            session.board.at(&Point::new(1, 1)).unwrap().state(),
+            &MapElementCellState::Closed
+        );
+        // the replay follows the moves that are left
+        assert!(session.apply(&Action::Open(Point::new(0, 0))));
+        let replay = session.replay.unwrap();
+        assert_eq!(replay.actions.len(), 1);
+        assert_eq!(replay.actions[0].action, Action::Open(Point::new(0, 0)));
+    }
+
+    #[test]
+    fn test_robot() {
+        let mut session = session();
+        // nothing is known before the first click
+        assert_eq!(session.auto(), 0);
+        assert!(!session.assisted);
+        assert!(matches!(session.hint(), Some(Move::Guess { .. })));
+        assert!(session.apply(&Action::Open(Point::new(0, 0))));
+        assert!(session.auto() > 0);
+        assert_eq!(session.board.state, BoardState::Won);
+        let finished = session.finished().unwrap();
+        assert!(finished.assisted);
+        assert_eq!(session.robot(), None);
+    }
+}
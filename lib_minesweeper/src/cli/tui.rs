@@ -0,0 +1,374 @@
+//! Full-screen play: move around the board with the arrows or hjkl, open
+//! with space, flag with f, or just click on cells. The board is drawn with
+//! the same colours as the line-based prompt. u undoes, ? asks for a hint
+//! and r and a let the robot play one move or as far as it can.
+
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+    KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use crossterm::style::Print;
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+
+use lib_minesweeper::{Action, Board, BoardState, MapElement, MapElementCellState, Point};
+
+use crate::cli::coords::{column_label, row_label};
+use crate::cli::render::{self, Style};
+use crate::cli::session::{describe, Finished, Session};
+use crate::column_label_lines;
+
+/// How often the timer is redrawn while waiting for input.
+const TICK: Duration = Duration::from_millis(250);
+
+/// Puts the terminal back the way it was, even if drawing fails halfway.
+struct Screen;
+
+impl Screen {
+    fn enter() -> io::Result<Screen> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture, Hide)?;
+        Ok(Screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(
+            io::stdout(),
+            Show,
+            DisableMouseCapture,
+            LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+struct Tui {
+    session: Session,
+    cursor: Point,
+    /// What the robot said, shown in place of the keys until the next one.
+    message: Option<String>,
+    style: Style,
+}
+
+/// Plays `board` full screen, drawn in `style`, until it's finished, or
+/// `None` if the player quits first.
+pub fn play(board: Board, style: Style) -> io::Result<Option<Finished>> {
+    let screen = Screen::enter()?;
+    let mut tui = Tui {
+        cursor: Point::new(board.width / 2, board.height / 2),
+        session: Session::new(board),
+        message: None,
+        style,
+    };
+    loop {
+        tui.draw()?;
+        if !event::poll(TICK)? {
+            continue;
+        }
+        let quit = match event::read()? {
+            Event::Key(key) => tui.key(key),
+            Event::Mouse(mouse) => {
+                tui.mouse(mouse);
+                false
+            }
+            _ => false,
+        };
+        if quit {
+            break;
+        }
+    }
+    drop(screen);
+    Ok(tui.session.finished())
+}
+
+impl Tui {
+    fn board(&self) -> &Board {
+        &self.session.board
+    }
+
+    fn cell_width(&self) -> usize {
+        render::cell_width(self.style, &self.board().variant)
+    }
+
+    /// Lines above the board: the status bar and the column labels.
+    fn top(&self) -> u16 {
+        1 + column_label(self.board().width - 1).len() as u16
+    }
+
+    fn label_width(&self) -> usize {
+        row_label(self.board().height - 1).len()
+    }
+
+    /// Whether to stop, which is on q or escape, or any key but undo once
+    /// the game is over.
+    fn key(&mut self, key: KeyEvent) -> bool {
+        if key.kind == KeyEventKind::Release {
+            return false;
+        }
+        self.message = None;
+        if key.code == KeyCode::Char('u') {
+            self.session.undo();
+            return false;
+        }
+        if self.board().is_finished() {
+            return true;
+        }
+        let (dx, dy) = match key.code {
+            KeyCode::Left | KeyCode::Char('h') => (-1, 0),
+            KeyCode::Right | KeyCode::Char('l') => (1, 0),
+            KeyCode::Up | KeyCode::Char('k') => (0, -1),
+            KeyCode::Down | KeyCode::Char('j') => (0, 1),
+            KeyCode::Char(' ') | KeyCode::Enter => {
+                self.open(self.cursor);
+                (0, 0)
+            }
+            KeyCode::Char('f') => {
+                self.apply(Action::Flag(self.cursor));
+                (0, 0)
+            }
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return true,
+            KeyCode::Char('c') => {
+                self.apply(Action::Chord(self.cursor));
+                (0, 0)
+            }
+            KeyCode::Char('?') => {
+                self.message = Some(match self.session.hint() {
+                    Some(next) => describe(&next),
+                    None => "the robot has nothing to suggest".into(),
+                });
+                (0, 0)
+            }
+            KeyCode::Char('r') => {
+                if let Some(next) = self.session.robot() {
+                    self.message = Some(describe(&next));
+                }
+                (0, 0)
+            }
+            KeyCode::Char('a') => {
+                self.message = Some(format!("the robot made {} moves", self.session.auto()));
+                (0, 0)
+            }
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            _ => (0, 0),
+        };
+        self.cursor = Point {
+            x: (self.cursor.x + dx).clamp(0, self.board().width as i32 - 1),
+            y: (self.cursor.y + dy).clamp(0, self.board().height as i32 - 1),
+        };
+        false
+    }
+
+    fn mouse(&mut self, mouse: MouseEvent) {
+        let MouseEventKind::Down(button) = mouse.kind else {
+            return;
+        };
+        let Some(p) = self.cell_at(mouse.column, mouse.row) else {
+            return;
+        };
+        self.cursor = p;
+        match button {
+            MouseButton::Left => self.open(p),
+            MouseButton::Right => self.apply(Action::Flag(p)),
+            MouseButton::Middle => self.apply(Action::Chord(p)),
+        }
+    }
+
+    /// The cell drawn at a position on screen.
+    fn cell_at(&self, column: u16, row: u16) -> Option<Point> {
+        let left = self.label_width() + 1;
+        let step = self.cell_width() + 1;
+        let x = (column as usize).checked_sub(left)? / step;
+        let y = row.checked_sub(self.top())? as usize;
+        Some(Point::new(x, y)).filter(|p| self.board().at(p).is_some())
+    }
+
+    /// Opens `p`, or the cells around it if it's a number that's open already.
+    fn open(&mut self, p: Point) {
+        match self.board().at(&p) {
+            Some(MapElement::Number {
+                state: MapElementCellState::Open,
+                ..
+            }) => self.apply(Action::Chord(p)),
+            _ => self.apply(Action::Open(p)),
+        }
+    }
+
+    fn apply(&mut self, action: Action) {
+        self.session.apply(&action);
+    }
+
+    fn status(&self) -> String {
+        let flags = self
+            .board()
+            .points()
+            .filter(|p| {
+                matches!(
+                    self.board().at(p).unwrap().state(),
+                    MapElementCellState::Flagged(_)
+                )
+            })
+            .count();
+        // plain styles spell out what the emoji stand for
+        let [time, mines, lives, won, failed, robot, dot] = if self.style == Style::Colour {
+            ["⏱", "💣", "❤️", "🎉 WON!", "☠️ FAILED,", "🤖", "·"]
+        } else {
+            ["time", "mines", "lives", "WON!", "FAILED,", "robot:", "|"]
+        };
+        let mut status = format!(
+            "{} {:>3}  {} {:>3}",
+            time,
+            self.session.elapsed() as u64,
+            mines,
+            self.board().mines as i64 - flags as i64
+        );
+        if let Some(left) = self.board().lives {
+            status += &format!("  {} {}", lives, left);
+        }
+        match self.board().state {
+            BoardState::Won => status += &format!("  {} u undo {} any other key quits", won, dot),
+            BoardState::Failed => {
+                status += &format!("  {} u undo {} any other key quits", failed, dot)
+            }
+            _ => (),
+        };
+        match &self.message {
+            Some(message) => status += &format!("  {} {}", robot, message),
+            None if !self.board().is_finished() => {
+                let keys = [
+                    "arrows/hjkl move",
+                    "space open",
+                    "f flag",
+                    "c chord",
+                    "u undo",
+                    "? hint",
+                    "r robot",
+                    "a auto",
+                    "q quit",
+                ];
+                status += &format!("  {}", keys.join(&format!(" {} ", dot)));
+            }
+            None => (),
+        };
+        status
+    }
+
+    fn draw(&self) -> io::Result<()> {
+        let cell_width = self.cell_width();
+        let label_width = self.label_width();
+        let mut lines = vec![self.status()];
+        lines.extend(column_label_lines(
+            self.board().width,
+            cell_width,
+            label_width,
+        ));
+
+        // lost boards come with their own post-mortem, won ones are shown in full
+        let is_done = self.board().state == BoardState::Won;
+        for y in 0..self.board().height {
+            let mut line = format!("{:>1$}", row_label(y), label_width);
+            for x in 0..self.board().width {
+                let p = Point::new(x, y);
+                // brackets around the cursor, in the gaps between cells
+                line.push(
+                    match (
+                        p == self.cursor,
+                        x > 0 && Point::new(x - 1, y) == self.cursor,
+                    ) {
+                        (true, _) => '[',
+                        (_, true) => ']',
+                        _ => ' ',
+                    },
+                );
+                line += &render::cell(
+                    self.style,
+                    self.board().at(&p).unwrap(),
+                    self.board().has_surrounding_mines(&p),
+                    &self.board().variant,
+                    is_done,
+                );
+            }
+            if self.cursor == Point::new(self.board().width - 1, y) {
+                line.push(']');
+            }
+            lines.push(line);
+        }
+
+        let mut out = io::stdout();
+        for (y, line) in lines.iter().enumerate() {
+            queue!(
+                out,
+                MoveTo(0, y as u16),
+                Print(line),
+                Clear(ClearType::UntilNewLine)
+            )?;
+        }
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_minesweeper::{create_board_with_mines, numbers_on_board, MineKind};
+    use pretty_assertions::assert_eq;
+
+    fn tui() -> Tui {
+        let board = numbers_on_board(create_board_with_mines(
+            30,
+            3,
+            &[(Point::new(29, 2), MineKind::Positive)],
+        ));
+        Tui {
+            cursor: Point::new(0, 0),
+            session: Session::new(board),
+            message: None,
+            style: Style::Colour,
+        }
+    }
+
+    fn press(tui: &mut Tui, code: KeyCode) -> bool {
+        tui.key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_keys() {
+        let mut tui = tui();
+        assert!(!press(&mut tui, KeyCode::Left));
+        assert!(!press(&mut tui, KeyCode::Char('j')));
+        assert!(!press(&mut tui, KeyCode::Right));
+        assert_eq!(tui.cursor, Point::new(1, 1));
+        press(&mut tui, KeyCode::Char('f'));
+        assert_eq!(
+            tui.board().at(&Point::new(1, 1)).unwrap().state(),
+            &MapElementCellState::Flagged(MineKind::Positive)
+        );
+        press(&mut tui, KeyCode::Char('f'));
+        press(&mut tui, KeyCode::Char(' '));
+        assert_eq!(tui.board().state, BoardState::Won);
+        assert!(tui.session.finished().is_some());
+        assert!(!press(&mut tui, KeyCode::Char('u')));
+        assert_eq!(tui.board().state, BoardState::Playing);
+        press(&mut tui, KeyCode::Char('r'));
+        assert_eq!(tui.board().state, BoardState::Won);
+        assert!(tui.session.assisted);
+        assert!(press(&mut tui, KeyCode::Char('x')));
+    }
+
+    #[test]
+    fn test_cell_at() {
+        let tui = tui();
+        // a status line and two lines of labels, then a 1 wide row label
+        assert_eq!(tui.top(), 3);
+        assert_eq!(tui.cell_at(2, 3), Some(Point::new(0, 0)));
+        assert_eq!(tui.cell_at(5, 4), Some(Point::new(1, 1)));
+        assert_eq!(tui.cell_at(2, 2), None);
+        assert_eq!(tui.cell_at(0, 3), None);
+        assert_eq!(tui.cell_at(200, 3), None);
+    }
+}
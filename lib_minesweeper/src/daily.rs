@@ -0,0 +1,113 @@
+use std::fmt;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::difficulty::BoardSize;
+use crate::{create_board, numbers_on_board, seeded_rand, Board};
+
+/// A calendar day in UTC, so that everyone gets the same daily board at the
+/// same time.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct Day {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Day {
+    /// The day that is `days` after 1970-01-01.
+    pub fn from_days_since_epoch(days: i64) -> Day {
+        // Howard Hinnant's civil_from_days
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (yoe + era * 400 + if month <= 2 { 1 } else { 0 }) as i32;
+        Day { year, month, day }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn today() -> Day {
+        let elapsed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("clock is set before 1970");
+        Day::from_days_since_epoch((elapsed.as_secs() / 86_400) as i64)
+    }
+
+    /// The seed the day's board of `size` is made from.
+    pub fn seed(&self, size: &BoardSize) -> u64 {
+        // FNV-1a over everything that tells daily boards apart
+        [
+            self.year as u64,
+            self.month as u64,
+            self.day as u64,
+            size.width as u64,
+            size.height as u64,
+            size.mines as u64,
+        ]
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, n| {
+            (hash ^ n).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+    }
+}
+
+impl fmt::Display for Day {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// The daily challenge board of the given size, the same for everyone
+/// playing on `day`.
+pub fn daily_board(day: &Day, size: &BoardSize) -> Board {
+    let board = create_board(
+        size.width,
+        size.height,
+        size.mines,
+        seeded_rand(day.seed(size)),
+    );
+    numbers_on_board(board)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const SIZE: BoardSize = BoardSize {
+        width: 9,
+        height: 9,
+        mines: 10,
+    };
+
+    #[test]
+    fn test_from_days_since_epoch() {
+        assert_eq!(
+            Day::from_days_since_epoch(0),
+            Day {
+                year: 1970,
+                month: 1,
+                day: 1
+            }
+        );
+        assert_eq!(Day::from_days_since_epoch(11016).to_string(), "2000-02-29");
+        assert_eq!(Day::from_days_since_epoch(20745).to_string(), "2026-10-19");
+    }
+
+    #[test]
+    fn test_daily_board() {
+        let day = Day::from_days_since_epoch(20745);
+        let board = daily_board(&day, &SIZE);
+        assert_eq!(board, daily_board(&day, &SIZE));
+        assert_eq!(board.mines, 10);
+        assert_ne!(
+            board,
+            daily_board(&Day::from_days_since_epoch(20746), &SIZE)
+        );
+    }
+}
@@ -0,0 +1,207 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::Board;
+
+/// Dimensions and mine count, which is what makes games comparable.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct BoardSize {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+}
+
+impl BoardSize {
+    pub fn of(board: &Board) -> BoardSize {
+        BoardSize {
+            width: board.width,
+            height: board.height,
+            mines: board.mines,
+        }
+    }
+}
+
+impl fmt::Display for BoardSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}/{}", self.width, self.height, self.mines)
+    }
+}
+
+pub const MIN_SIDE: usize = 2;
+pub const MAX_SIDE: usize = 100;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum Difficulty {
+    /// 9x9 with 10 mines.
+    Beginner,
+    /// 16x16 with 40 mines.
+    Intermediate,
+    /// 30x16 with 99 mines.
+    Expert,
+    /// Any size that passes `Difficulty::custom`.
+    Custom(BoardSize),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum DifficultyError {
+    /// Width or height outside `MIN_SIDE..=MAX_SIDE`.
+    BadSide(usize),
+    NoMines,
+    /// More mines than there are cells to put them in, leaving at least one
+    /// cell free.
+    TooManyMines {
+        mines: usize,
+        max: usize,
+    },
+    /// A preset name that isn't one of ours.
+    UnknownPreset(String),
+}
+
+impl fmt::Display for DifficultyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DifficultyError::BadSide(side) => write!(
+                f,
+                "boards must be between {} and {} cells across, not {}",
+                MIN_SIDE, MAX_SIDE, side
+            ),
+            DifficultyError::NoMines => write!(f, "boards need at least one mine"),
+            DifficultyError::TooManyMines { mines, max } => write!(
+                f,
+                "{} mines don't fit, this board takes at most {}",
+                mines, max
+            ),
+            DifficultyError::UnknownPreset(name) => write!(
+                f,
+                "unknown difficulty '{}', expected beginner, intermediate or expert",
+                name
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DifficultyError {}
+
+impl Difficulty {
+    pub const PRESETS: [Difficulty; 3] = [
+        Difficulty::Beginner,
+        Difficulty::Intermediate,
+        Difficulty::Expert,
+    ];
+
+    /// A custom difficulty, if a board of that size can be played.
+    pub fn custom(
+        width: usize,
+        height: usize,
+        mines: usize,
+    ) -> Result<Difficulty, DifficultyError> {
+        for side in [width, height] {
+            if !(MIN_SIDE..=MAX_SIDE).contains(&side) {
+                return Err(DifficultyError::BadSide(side));
+            }
+        }
+        if mines == 0 {
+            return Err(DifficultyError::NoMines);
+        }
+        let max = width * height - 1;
+        if mines > max {
+            return Err(DifficultyError::TooManyMines { mines, max });
+        }
+        Ok(Difficulty::Custom(BoardSize {
+            width,
+            height,
+            mines,
+        }))
+    }
+
+    pub fn size(&self) -> BoardSize {
+        let (width, height, mines) = match self {
+            Difficulty::Beginner => (9, 9, 10),
+            Difficulty::Intermediate => (16, 16, 40),
+            Difficulty::Expert => (30, 16, 99),
+            Difficulty::Custom(size) => return *size,
+        };
+        BoardSize {
+            width,
+            height,
+            mines,
+        }
+    }
+
+    /// The preset after this one, wrapping around. Custom sizes go back to
+    /// the first preset.
+    pub fn next(&self) -> Difficulty {
+        match self {
+            Difficulty::Beginner => Difficulty::Intermediate,
+            Difficulty::Intermediate => Difficulty::Expert,
+            Difficulty::Expert | Difficulty::Custom(_) => Difficulty::Beginner,
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Difficulty::Beginner => write!(f, "beginner"),
+            Difficulty::Intermediate => write!(f, "intermediate"),
+            Difficulty::Expert => write!(f, "expert"),
+            Difficulty::Custom(size) => write!(f, "custom {}", size),
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = DifficultyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "beginner" => Ok(Difficulty::Beginner),
+            "intermediate" => Ok(Difficulty::Intermediate),
+            "expert" => Ok(Difficulty::Expert),
+            _ => Err(DifficultyError::UnknownPreset(s.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_presets() {
+        let sizes: Vec<String> = Difficulty::PRESETS
+            .iter()
+            .map(|d| d.size().to_string())
+            .collect();
+        assert_eq!(sizes, vec!["9x9/10", "16x16/40", "30x16/99"]);
+        assert_eq!("Expert".parse(), Ok(Difficulty::Expert));
+        assert_eq!(
+            "hard".parse::<Difficulty>(),
+            Err(DifficultyError::UnknownPreset("hard".into()))
+        );
+    }
+
+    #[test]
+    fn test_custom() {
+        assert_eq!(
+            Difficulty::custom(40, 20, 150).map(|d| d.size().to_string()),
+            Ok(String::from("40x20/150"))
+        );
+        assert_eq!(
+            Difficulty::custom(1, 9, 1),
+            Err(DifficultyError::BadSide(1))
+        );
+        assert_eq!(
+            Difficulty::custom(9, 101, 1),
+            Err(DifficultyError::BadSide(101))
+        );
+        assert_eq!(Difficulty::custom(9, 9, 0), Err(DifficultyError::NoMines));
+        assert_eq!(
+            Difficulty::custom(3, 3, 9),
+            Err(DifficultyError::TooManyMines { mines: 9, max: 8 })
+        );
+    }
+}
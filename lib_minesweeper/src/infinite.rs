@@ -0,0 +1,374 @@
+//! A board without edges for score attack: see how far you get before
+//! hitting a mine. The plane is split into square chunks whose mines only
+//! depend on the seed and the chunk's coordinates, so chunks are generated
+//! when play first reaches them and come out the same in any order.
+//!
+//! The mine density is kept low enough that empty areas can't go on forever,
+//! so cascades always end, crossing into as many chunks as they need.
+
+use std::collections::HashMap;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{seeded_rand, Action, BoardState, MapElement, MapElementCellState, MineKind, Point};
+
+/// Side of a chunk, in cells.
+pub const CHUNK_SIZE: i32 = 16;
+/// Mines per chunk, the density of an intermediate board.
+pub const CHUNK_MINES: usize = 40;
+
+/// Coordinates of a chunk, counted in chunks from the one with the origin.
+pub type ChunkPoint = (i32, i32);
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct InfiniteBoard {
+    pub seed: u64,
+    /// Chunks play has reached, row by row.
+    chunks: HashMap<ChunkPoint, Vec<MapElement>>,
+    pub state: BoardState,
+    /// Safe cells opened so far, which is the score.
+    pub opened: usize,
+}
+
+/// The chunk `p` is in and its index inside the chunk.
+fn locate(p: &Point) -> (ChunkPoint, usize) {
+    let chunk = (p.x.div_euclid(CHUNK_SIZE), p.y.div_euclid(CHUNK_SIZE));
+    let index = p.y.rem_euclid(CHUNK_SIZE) * CHUNK_SIZE + p.x.rem_euclid(CHUNK_SIZE);
+    (chunk, index as usize)
+}
+
+fn surrounding_points(p: &Point) -> impl Iterator<Item = Point> + '_ {
+    (-1..=1)
+        .flat_map(move |dy| {
+            (-1..=1).map(move |dx| Point {
+                x: p.x + dx,
+                y: p.y + dy,
+            })
+        })
+        .filter(move |n| n != p)
+}
+
+/// Which cells of `chunk` are mines. Cells around the origin never are, so
+/// there's always somewhere safe to start.
+fn chunk_mines(seed: u64, chunk: ChunkPoint) -> Vec<bool> {
+    // FNV-1a, like the daily boards
+    let chunk_seed = [seed, chunk.0 as u64, chunk.1 as u64]
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, n| {
+            (hash ^ n).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+    let mut rand = seeded_rand(chunk_seed);
+    let cells = (CHUNK_SIZE * CHUNK_SIZE) as usize;
+    let mut order: Vec<usize> = (0..cells).collect();
+    let mut mines = vec![false; cells];
+    for i in 0..CHUNK_MINES {
+        order.swap(i, rand(i, cells));
+        mines[order[i]] = true;
+    }
+    for y in -1..=1 {
+        for x in -1..=1 {
+            let (origin, index) = locate(&Point { x, y });
+            if origin == chunk {
+                mines[index] = false;
+            }
+        }
+    }
+    mines
+}
+
+/// The cells of `chunk`, all closed.
+fn generate(seed: u64, chunk: ChunkPoint) -> Vec<MapElement> {
+    // counts near the edges need the mines of the chunks around
+    let mut mines = HashMap::new();
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            let around = (chunk.0 + dx, chunk.1 + dy);
+            mines.insert(around, chunk_mines(seed, around));
+        }
+    }
+    let is_mine = |p: &Point| {
+        let (chunk, index) = locate(p);
+        mines[&chunk][index]
+    };
+    (0..CHUNK_SIZE)
+        .flat_map(|y| (0..CHUNK_SIZE).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let p = Point {
+                x: chunk.0 * CHUNK_SIZE + x,
+                y: chunk.1 * CHUNK_SIZE + y,
+            };
+            if is_mine(&p) {
+                MapElement::Mine {
+                    state: MapElementCellState::Closed,
+                    kind: MineKind::Positive,
+                }
+            } else {
+                MapElement::Number {
+                    state: MapElementCellState::Closed,
+                    count: surrounding_points(&p).filter(is_mine).count() as i32,
+                    secondary_count: 0,
+                }
+            }
+        })
+        .collect()
+}
+
+impl InfiniteBoard {
+    pub fn new(seed: u64) -> InfiniteBoard {
+        InfiniteBoard {
+            seed,
+            chunks: HashMap::new(),
+            state: BoardState::Ready,
+            opened: 0,
+        }
+    }
+
+    /// The cell at `p`, working it out if play hasn't reached it yet.
+    pub fn at(&self, p: &Point) -> MapElement {
+        let (chunk, index) = locate(p);
+        match self.chunks.get(&chunk) {
+            Some(cells) => cells[index].clone(),
+            None => generate(self.seed, chunk)[index].clone(),
+        }
+    }
+
+    /// The `width` by `height` cells from `origin`, row by row. Chunks play
+    /// hasn't reached are worked out once each, not once per cell.
+    pub fn region(&self, origin: &Point, width: usize, height: usize) -> Vec<Vec<MapElement>> {
+        let mut generated = HashMap::new();
+        let mut rows = vec![];
+        for y in 0..height as i32 {
+            let mut row = vec![];
+            for x in 0..width as i32 {
+                let (chunk, index) = locate(&Point {
+                    x: origin.x + x,
+                    y: origin.y + y,
+                });
+                let cells = match self.chunks.get(&chunk) {
+                    Some(cells) => cells,
+                    None => generated
+                        .entry(chunk)
+                        .or_insert_with(|| generate(self.seed, chunk)),
+                };
+                row.push(cells[index].clone());
+            }
+            rows.push(row);
+        }
+        rows
+    }
+
+    /// How many chunks play has reached.
+    pub fn chunks(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state == BoardState::Failed
+    }
+
+    fn cell_mut(&mut self, p: &Point) -> &mut MapElement {
+        let (chunk, index) = locate(p);
+        let seed = self.seed;
+        &mut self
+            .chunks
+            .entry(chunk)
+            .or_insert_with(|| generate(seed, chunk))[index]
+    }
+
+    fn set_state(&mut self, p: &Point, state: MapElementCellState) {
+        let cell = self.cell_mut(p);
+        *cell = match cell {
+            MapElement::Mine { kind, .. } => MapElement::Mine {
+                state,
+                kind: kind.clone(),
+            },
+            MapElement::Number {
+                count,
+                secondary_count,
+                ..
+            } => MapElement::Number {
+                state,
+                count: *count,
+                secondary_count: *secondary_count,
+            },
+        };
+    }
+
+    /// Opens `p`, and everything around it while the cells opened are 0s.
+    fn open(&mut self, p: &Point) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+        let mut pending = vec![*p];
+        let mut changed = false;
+        while let Some(p) = pending.pop() {
+            match self.cell_mut(&p).clone() {
+                MapElement::Number {
+                    state: MapElementCellState::Closed | MapElementCellState::Questioned,
+                    count,
+                    ..
+                } => {
+                    self.set_state(&p, MapElementCellState::Open);
+                    self.opened += 1;
+                    changed = true;
+                    if count == 0 {
+                        pending.extend(surrounding_points(&p));
+                    }
+                }
+                MapElement::Mine {
+                    state: MapElementCellState::Closed | MapElementCellState::Questioned,
+                    ..
+                } => {
+                    self.set_state(&p, MapElementCellState::Exploded);
+                    self.state = BoardState::Failed;
+                    return true;
+                }
+                _ => (),
+            }
+        }
+        if changed {
+            self.state = BoardState::Playing;
+        }
+        changed
+    }
+
+    fn flag(&mut self, p: &Point) -> bool {
+        let state = match self.cell_mut(p).state() {
+            MapElementCellState::Closed => MapElementCellState::Flagged(MineKind::Positive),
+            MapElementCellState::Flagged(_) => MapElementCellState::Closed,
+            _ => return false,
+        };
+        self.set_state(p, state);
+        true
+    }
+
+    /// Opens the closed cells around the number at `p` once it has as many
+    /// flags around it as it counts.
+    fn chord(&mut self, p: &Point) -> bool {
+        let MapElement::Number {
+            state: MapElementCellState::Open,
+            count,
+            ..
+        } = self.at(p)
+        else {
+            return false;
+        };
+        let neighbours: Vec<Point> = surrounding_points(p).collect();
+        let flagged = neighbours
+            .iter()
+            .filter(|n| matches!(self.at(n).state(), MapElementCellState::Flagged(_)))
+            .count() as i32;
+        if flagged != count {
+            return false;
+        }
+        let mut changed = false;
+        for n in &neighbours {
+            changed |= self.open(n);
+        }
+        changed
+    }
+
+    /// Applies `action`, or returns `None` if it wouldn't change the board.
+    pub fn apply(&self, action: &Action) -> Option<InfiniteBoard> {
+        if self.is_finished() {
+            return None;
+        }
+        let mut board = self.clone();
+        let changed = match action {
+            Action::Open(p) => board.open(p),
+            Action::Flag(p) => board.flag(p),
+            Action::Chord(p) => board.chord(p),
+        };
+        Some(board).filter(|_| changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn is_mine(board: &InfiniteBoard, p: &Point) -> bool {
+        matches!(board.at(p), MapElement::Mine { .. })
+    }
+
+    #[test]
+    fn test_chunks_only_depend_on_the_seed() {
+        let board = InfiniteBoard::new(42);
+        let far = Point { x: -100, y: 57 };
+        let opened = board.apply(&Action::Open(Point { x: 0, y: 0 })).unwrap();
+        // whether a chunk was generated already doesn't change it
+        assert_eq!(board.at(&far), opened.at(&far));
+        // across chunks, some reached and some not
+        let origin = Point { x: -20, y: 10 };
+        let region = opened.region(&origin, 40, 3);
+        assert_eq!(region.len(), 3);
+        assert_eq!(
+            region[2][39],
+            opened.at(&Point {
+                x: origin.x + 39,
+                y: origin.y + 2
+            })
+        );
+        assert_eq!(
+            InfiniteBoard::new(42).at(&Point { x: 20, y: -3 }),
+            opened.at(&Point { x: 20, y: -3 })
+        );
+        let (chunk, _) = locate(&far);
+        let mines = chunk_mines(42, chunk).iter().filter(|m| **m).count();
+        assert_eq!(mines, CHUNK_MINES);
+        assert_ne!(chunk_mines(42, chunk), chunk_mines(43, chunk));
+    }
+
+    #[test]
+    fn test_counts_cross_chunks() {
+        let board = InfiniteBoard::new(7);
+        for p in [
+            Point { x: -1, y: -1 },
+            Point { x: 15, y: 16 },
+            Point { x: -17, y: 31 },
+        ] {
+            if let MapElement::Number { count, .. } = board.at(&p) {
+                let mines = surrounding_points(&p)
+                    .filter(|n| is_mine(&board, n))
+                    .count();
+                assert_eq!(count as usize, mines);
+            }
+        }
+    }
+
+    #[test]
+    fn test_open_cascades_across_chunks() {
+        let board = InfiniteBoard::new(1)
+            .apply(&Action::Open(Point { x: 0, y: 0 }))
+            .unwrap();
+        assert_eq!(board.state, BoardState::Playing);
+        // the origin sits on the corner of four chunks
+        assert_eq!(
+            board.at(&Point { x: -1, y: -1 }).state(),
+            &MapElementCellState::Open
+        );
+        assert_eq!(
+            board.at(&Point { x: 1, y: 1 }).state(),
+            &MapElementCellState::Open
+        );
+        assert!(board.chunks() >= 4);
+        assert!(board.opened >= 9);
+        assert_eq!(board.apply(&Action::Open(Point { x: 0, y: 0 })), None);
+    }
+
+    #[test]
+    fn test_open_mine() {
+        let board = InfiniteBoard::new(1);
+        let mine = (2..)
+            .map(|x| Point { x, y: 0 })
+            .find(|p| is_mine(&board, p))
+            .unwrap();
+        let flagged = board.apply(&Action::Flag(mine)).unwrap();
+        assert_eq!(flagged.apply(&Action::Open(mine)), None);
+        let failed = board.apply(&Action::Open(mine)).unwrap();
+        assert_eq!(failed.state, BoardState::Failed);
+        assert_eq!(failed.opened, 0);
+        assert_eq!(failed.apply(&Action::Open(Point { x: 0, y: 0 })), None);
+    }
+}
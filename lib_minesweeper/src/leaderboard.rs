@@ -0,0 +1,40 @@
+//! What frontends and the leaderboard server send each other.
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::difficulty::BoardSize;
+use crate::rating::Rating;
+use crate::replay::{Claim, Replay};
+
+/// A finished game sent in for the leaderboard.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Submission {
+    pub player: String,
+    pub replay: Replay,
+    pub claim: Claim,
+}
+
+/// A verified game on the leaderboard.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub player: String,
+    pub size: BoardSize,
+    pub seconds: f64,
+    pub three_bv: usize,
+    pub three_bv_per_second: f64,
+}
+
+/// A player's standing on the ladder.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct PlayerRating {
+    pub player: String,
+    pub rating: Rating,
+}
+
+/// Path of the top times for boards of `size`, relative to the server's URL.
+pub fn top_path(size: &BoardSize) -> String {
+    format!(
+        "/top?width={}&height={}&mines={}",
+        size.width, size.height, size.mines
+    )
+}
@@ -1,29 +1,120 @@
+use std::collections::HashSet;
+
 use serde_derive::{Deserialize, Serialize};
 
+pub mod analysis;
+pub mod daily;
+pub mod difficulty;
+pub mod infinite;
+pub mod leaderboard;
+pub mod rating;
+pub mod replay;
+pub mod score;
+pub mod solver;
+pub mod stats;
+pub mod svg;
+pub mod text;
+
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum MapElement {
     Mine {
         state: MapElementCellState,
+        kind: MineKind,
     },
     Number {
         state: MapElementCellState,
         count: i32,
+        /// Secondary mines around this cell, only used in the two-colour variant.
+        secondary_count: i32,
     },
 }
+
+impl MapElement {
+    pub fn state(&self) -> &MapElementCellState {
+        match self {
+            Mine { state, .. } | Number { state, .. } => state,
+        }
+    }
+
+    fn with_state(&self, state: MapElementCellState) -> MapElement {
+        match self {
+            Mine { kind, .. } => Mine {
+                state,
+                kind: kind.clone(),
+            },
+            Number {
+                count,
+                secondary_count,
+                ..
+            } => Number {
+                state,
+                count: *count,
+                secondary_count: *secondary_count,
+            },
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum MapElementCellState {
     Closed,
     Open,
-    Flagged,
+    /// Flagged by the player as hiding a mine of the given kind.
+    Flagged(MineKind),
+    /// Marked by the player as uncertain, otherwise behaves as closed.
+    Questioned,
+    /// A mine the player opened.
+    Exploded,
+    /// A mine left unflagged, shown once the game is lost.
+    Revealed,
+    /// A flag placed where there is no mine of the flagged kind, shown once
+    /// the game is lost.
+    WronglyFlagged,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub enum MineKind {
+    /// Adds one to the count of its neighbours.
+    Positive,
+    /// Subtracts one from the count of its neighbours.
+    Negative,
+    /// Second colour of the two-colour variant, adds one to the secondary
+    /// count of its neighbours.
+    Secondary,
+}
+
+impl MineKind {
+    /// How much this mine adds to its neighbours' `(count, secondary_count)`.
+    fn weights(&self) -> (i32, i32) {
+        match self {
+            MineKind::Positive => (1, 0),
+            MineKind::Negative => (-1, 0),
+            MineKind::Secondary => (0, 1),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub enum Variant {
+    Classic,
+    /// Some mines are negative, so counts can be below zero, or zero even
+    /// when there are mines around.
+    NegativeMines,
+    /// Mines come in two colours and numbers count each colour separately.
+    TwoColour,
 }
 
 use MapElement::Mine;
 use MapElement::Number;
 use MapElementCellState::Closed;
+use MapElementCellState::Exploded;
 use MapElementCellState::Flagged;
 use MapElementCellState::Open;
+use MapElementCellState::Questioned;
+use MapElementCellState::Revealed;
+use MapElementCellState::WronglyFlagged;
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct Point {
     pub x: i32,
     pub y: i32,
@@ -46,6 +137,14 @@ pub enum BoardState {
     Failed,
 }
 
+/// Something the player does on a point of the board.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub enum Action {
+    Open(Point),
+    Flag(Point),
+    Chord(Point),
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct Board {
     map: Vec<Vec<MapElement>>,
@@ -54,6 +153,15 @@ pub struct Board {
     pub height: usize,
     pub mines: usize,
     pub state: BoardState,
+    pub variant: Variant,
+    /// Whether flagging cycles through a question mark before closing again.
+    pub question_marks: bool,
+    /// Mine hits the player can still survive, `None` when the first one ends
+    /// the game.
+    pub lives: Option<u32>,
+    /// Whether mines move around so that forced guesses are always safe and
+    /// needless ones never are. Only used on classic boards.
+    pub fair_guesses: bool,
 }
 
 impl Board {
@@ -63,6 +171,21 @@ impl Board {
             .flat_map(|x| x.iter())
             .filter(|x| matches!(x, Mine { .. }))
             .count();
+        let variant = map
+            .iter()
+            .flat_map(|x| x.iter())
+            .find_map(|x| match x {
+                Mine {
+                    kind: MineKind::Negative,
+                    ..
+                } => Some(Variant::NegativeMines),
+                Mine {
+                    kind: MineKind::Secondary,
+                    ..
+                } => Some(Variant::TwoColour),
+                _ => None,
+            })
+            .unwrap_or(Variant::Classic);
         let width = map.first().unwrap().len();
         let height = map.len();
         Board {
@@ -71,10 +194,86 @@ impl Board {
             mines,
             missing_points: (width as i32) * (height as i32) - (mines as i32),
             state: BoardState::NotReady,
+            variant,
+            question_marks: false,
+            lives: None,
+            fair_guesses: false,
             map,
         }
     }
 
+    /// Where the mines are and of which kind, whatever their state.
+    pub fn mine_layout(&self) -> Vec<(Point, MineKind)> {
+        self.points()
+            .filter_map(|p| match self.at(&p) {
+                Some(Mine { kind, .. }) => Some((p, kind.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn with_lives(self, lives: u32) -> Board {
+        Board {
+            lives: Some(lives),
+            ..self
+        }
+    }
+
+    pub fn with_question_marks(self, question_marks: bool) -> Board {
+        Board {
+            question_marks,
+            ..self
+        }
+    }
+
+    pub fn with_fair_guesses(self, fair_guesses: bool) -> Board {
+        Board {
+            fair_guesses,
+            ..self
+        }
+    }
+
+    /// The same position with the mines moved to `mines`, keeping every
+    /// cell's state and counting the numbers again. Only for classic boards.
+    pub(crate) fn with_mines_at(&self, mines: &[Point]) -> Board {
+        let mines: HashSet<&Point> = mines.iter().collect();
+        let map = (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(|x| {
+                        let p = Point::new(x, y);
+                        let state = self.at(&p).unwrap().state().clone();
+                        if mines.contains(&p) {
+                            Mine {
+                                state,
+                                kind: MineKind::Positive,
+                            }
+                        } else {
+                            Number {
+                                state,
+                                count: 0,
+                                secondary_count: 0,
+                            }
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let board = numbers_on_board(Board {
+            map,
+            ..self.clone()
+        });
+        Board {
+            state: self.state.clone(),
+            ..board
+        }
+    }
+
+    /// All points on the board, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        (0..self.height).flat_map(move |y| (0..self.width).map(move |x| Point::new(x, y)))
+    }
+
     pub fn at(&self, p: &Point) -> Option<&MapElement> {
         let width = self.width as i32;
         let height = self.height as i32;
@@ -88,7 +287,8 @@ impl Board {
     }
 
     fn replace(&self, p: &Point, el: MapElement) -> Board {
-        let was_closed = matches!(self.at(p), Some(Number { state: Closed, .. }));
+        let was_opened = matches!(el, Number { state: Open, .. })
+            && !matches!(self.at(p), Some(Number { state: Open, .. }));
         let map = (0..self.height)
             .map(|y| {
                 (0..self.width)
@@ -102,7 +302,7 @@ impl Board {
                     .collect()
             })
             .collect();
-        let missing_points = if was_closed {
+        let missing_points = if was_opened {
             self.missing_points - 1
         } else {
             self.missing_points
@@ -113,6 +313,10 @@ impl Board {
             mines: self.mines,
             missing_points,
             map,
+            variant: self.variant.clone(),
+            question_marks: self.question_marks,
+            lives: self.lives,
+            fair_guesses: self.fair_guesses,
             state: match (missing_points, &self.state) {
                 (0, _) => BoardState::Won,
                 (_, BoardState::Ready) => BoardState::Playing,
@@ -122,49 +326,56 @@ impl Board {
     }
 
     pub fn flag_item(&self, p: &Point) -> Board {
-        match self.at(p) {
-            Some(Mine { state }) => self.replace(
-                p,
-                Mine {
-                    state: match *state {
-                        Closed => Flagged,
-                        Flagged => Closed,
-                        Open => Open,
-                    },
-                },
-            ),
-            Some(Number { state, count }) => self.replace(
-                p,
-                Number {
-                    state: match *state {
-                        Closed => Flagged,
-                        Flagged => Closed,
-                        Open => Open,
-                    },
-                    count: *count,
-                },
-            ),
-            None => unreachable!(),
+        if self.is_finished() {
+            return self.clone();
+        }
+        let el = self.at(p).unwrap();
+        self.replace(p, el.with_state(self.next_flag_state(el.state())))
+    }
+
+    fn next_flag_state(&self, state: &MapElementCellState) -> MapElementCellState {
+        match (state, &self.variant) {
+            (Closed, _) => Flagged(MineKind::Positive),
+            (Flagged(MineKind::Positive), Variant::NegativeMines) => Flagged(MineKind::Negative),
+            (Flagged(MineKind::Positive), Variant::TwoColour) => Flagged(MineKind::Secondary),
+            (Flagged(_), _) if self.question_marks => Questioned,
+            (Flagged(_), _) | (Questioned, _) => Closed,
+            (state @ (Open | Exploded | Revealed | WronglyFlagged), _) => state.clone(),
         }
     }
 
+    /// Whether any mine surrounds `p`. With negative mines a count of 0 doesn't
+    /// imply this, so it's what decides if opening `p` cascades.
+    pub fn has_surrounding_mines(&self, p: &Point) -> bool {
+        self.surrounding_points(p)
+            .iter()
+            .any(|p| matches!(self.at(p), Some(Mine { .. })))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self.state, BoardState::Won | BoardState::Failed)
+    }
+
     pub fn cascade_open_item(&self, p: &Point) -> Option<Board> {
+        if self.is_finished() {
+            return None;
+        }
         match self.at(p).unwrap() {
             Number { state: Open, .. }
-            | Mine { state: Flagged, .. }
-            | Number { state: Flagged, .. } => None,
-            Number {
-                state: Closed,
+            | Mine {
+                state: Flagged(_) | Exploded,
+                ..
+            }
+            | Number {
+                state: Flagged(_), ..
+            } => None,
+            el @ Number {
+                state: Closed | Questioned,
                 count,
+                ..
             } => {
-                let board = self.replace(
-                    p,
-                    Number {
-                        state: Open,
-                        count: *count,
-                    },
-                );
-                if *count == 0 {
+                let board = self.replace(p, el.with_state(Open));
+                if *count == 0 && !board.has_surrounding_mines(p) {
                     Some(
                         board
                             .surrounding_points(p)
@@ -175,14 +386,122 @@ impl Board {
                     Some(board)
                 }
             }
-            Mine { state: Open } | Mine { state: Closed } => Some(Board {
-                map: self.map.clone(),
-                width: self.width,
-                height: self.height,
-                mines: self.mines,
-                missing_points: self.missing_points,
-                state: BoardState::Failed,
-            }),
+            el @ Mine {
+                state: Open | Closed | Questioned,
+                ..
+            } => match self.lives {
+                Some(lives) if lives > 0 => Some(Board {
+                    lives: Some(lives - 1),
+                    ..self.replace(p, el.with_state(Exploded))
+                }),
+                _ => Some(self.fail(p)),
+            },
+            Mine {
+                state: Revealed | WronglyFlagged,
+                ..
+            }
+            | Number {
+                state: Exploded | Revealed | WronglyFlagged,
+                ..
+            } => unreachable!(),
+        }
+    }
+
+    /// The board once the mine at `p` goes off: it explodes, wrong flags get
+    /// marked and the mines that were left unflagged are revealed.
+    fn fail(&self, p: &Point) -> Board {
+        let map = (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(|x| {
+                        let point = Point::new(x, y);
+                        let el = self.at(&point).unwrap();
+                        let state = match (el, el.state()) {
+                            (Mine { .. }, _) if point == *p => Exploded,
+                            (Mine { kind, .. }, Flagged(flag)) if flag != kind => WronglyFlagged,
+                            (Mine { .. }, Closed | Questioned) => Revealed,
+                            (Number { .. }, Flagged(_)) => WronglyFlagged,
+                            (_, state) => state.clone(),
+                        };
+                        el.with_state(state)
+                    })
+                    .collect()
+            })
+            .collect();
+        Board {
+            map,
+            width: self.width,
+            height: self.height,
+            mines: self.mines,
+            missing_points: self.missing_points,
+            state: BoardState::Failed,
+            variant: self.variant.clone(),
+            question_marks: self.question_marks,
+            lives: self.lives,
+            fair_guesses: self.fair_guesses,
+        }
+    }
+
+    /// Opens every closed neighbour of the open number at `p`, as long as the
+    /// flags around it add up to its count.
+    pub fn chord_item(&self, p: &Point) -> Option<Board> {
+        if self.is_finished() {
+            return None;
+        }
+        let Some(Number {
+            state: Open,
+            count,
+            secondary_count,
+        }) = self.at(p)
+        else {
+            return None;
+        };
+        let neighbours = self.surrounding_points(p);
+        let flagged = neighbours
+            .iter()
+            .filter_map(|n| match self.at(n).unwrap() {
+                Mine {
+                    state: Flagged(kind),
+                    ..
+                }
+                | Number {
+                    state: Flagged(kind),
+                    ..
+                }
+                | Mine {
+                    state: Exploded,
+                    kind,
+                } => Some(kind.weights()),
+                _ => None,
+            })
+            .fold((0, 0), |(a, b), (c, d)| (a + c, b + d));
+        if flagged != (*count, *secondary_count) {
+            return None;
+        }
+        let closed: Vec<_> = neighbours
+            .iter()
+            .filter(|n| matches!(self.at(n).unwrap().state(), Closed | Questioned))
+            .collect();
+        if closed.is_empty() {
+            return None;
+        }
+        Some(
+            closed
+                .iter()
+                .fold(self.clone(), |b, n| b.cascade_open_item(n).unwrap_or(b)),
+        )
+    }
+
+    /// Applies `action`, or returns `None` if it wouldn't change the board.
+    pub fn apply(&self, action: &Action) -> Option<Board> {
+        match action {
+            Action::Open(p) if self.fair_guesses => match solver::fair_layout(self, p) {
+                Some(board) => board.cascade_open_item(p),
+                None => self.cascade_open_item(p),
+            },
+            Action::Open(p) => self.cascade_open_item(p),
+            Action::Flag(p) => Some(self.flag_item(p)).filter(|b| b != self),
+            Action::Chord(p) => self.chord_item(p),
         }
     }
 
@@ -201,12 +520,18 @@ impl Board {
     }
 
     pub fn run_robot_on_point(&self, p: Point) -> Option<Board> {
+        // the robot's reasoning only holds when every mine adds to the count
+        if self.variant != Variant::Classic {
+            return None;
+        }
         let el = self.at(&p).unwrap();
         let Number {
             state: Open,
             count: mine_count,
-        } = el else {
-            return None
+            ..
+        } = el
+        else {
+            return None;
         };
         if *mine_count == 0 {
             return None;
@@ -220,7 +545,8 @@ impl Board {
                     el,
                     Number {
                         state: Open,
-                        count: 0
+                        count: 0,
+                        ..
                     }
                 )
             })
@@ -231,22 +557,50 @@ impl Board {
         let flagged_count = surrounding_els
             .iter()
             .filter(|(_p, el)| {
-                matches!(el, Mine { state: Flagged } | Number { state: Flagged, .. })
+                matches!(
+                    el,
+                    Mine {
+                        state: Flagged(_) | Exploded,
+                        ..
+                    } | Number {
+                        state: Flagged(_),
+                        ..
+                    }
+                )
             })
             .count();
         let unopened_count = unopened.clone().count();
         if *mine_count == unopened_count as i32 && flagged_count < unopened_count {
-            let (p, _el) = unopened
+            let (p, el) = unopened
                 .find(|(_p, el)| {
-                    !matches!(el, Mine { state: Flagged } | Number { state: Flagged, .. })
+                    !matches!(
+                        el,
+                        Mine {
+                            state: Flagged(_) | Exploded,
+                            ..
+                        } | Number {
+                            state: Flagged(_),
+                            ..
+                        }
+                    )
                 })
                 .unwrap();
-            return Some(self.flag_item(p));
+            // not flag_item, which would turn a question mark back to closed
+            return Some(self.replace(p, el.with_state(Flagged(MineKind::Positive))));
         }
         if *mine_count == flagged_count as i32 && unopened_count - flagged_count > 0 {
             let (p, _el) = unopened
                 .find(|(_p, el)| {
-                    !matches!(el, Mine { state: Flagged } | Number { state: Flagged, .. })
+                    !matches!(
+                        el,
+                        Mine {
+                            state: Flagged(_) | Exploded,
+                            ..
+                        } | Number {
+                            state: Flagged(_),
+                            ..
+                        }
+                    )
                 })
                 .unwrap();
             return self.cascade_open_item(p);
@@ -255,10 +609,74 @@ impl Board {
     }
 }
 
+/// A deterministic source of randomness for `create_board`, for boards that
+/// must come out the same everywhere they are generated.
+pub fn seeded_rand(seed: u64) -> impl FnMut(usize, usize) -> usize {
+    // splitmix64, which is tiny and good enough for placing mines
+    let mut state = seed;
+    move |low, high| {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        low + (z % (high - low) as u64) as usize
+    }
+}
+
 pub fn create_board(
     width: usize,
     height: usize,
     mines: usize,
+    rand: impl FnMut(usize, usize) -> usize,
+) -> Board {
+    create_board_with_kind(width, height, mines, 0, MineKind::Positive, rand)
+}
+
+/// Like `create_board`, but `negative_mines` out of the `mines` subtract from
+/// their neighbours' counts instead of adding to them.
+pub fn create_negative_mines_board(
+    width: usize,
+    height: usize,
+    mines: usize,
+    negative_mines: usize,
+    rand: impl FnMut(usize, usize) -> usize,
+) -> Board {
+    create_board_with_kind(
+        width,
+        height,
+        mines,
+        negative_mines,
+        MineKind::Negative,
+        rand,
+    )
+}
+
+/// Like `create_board`, but `secondary_mines` out of the `mines` are of the
+/// second colour and get counted separately.
+pub fn create_two_colour_board(
+    width: usize,
+    height: usize,
+    mines: usize,
+    secondary_mines: usize,
+    rand: impl FnMut(usize, usize) -> usize,
+) -> Board {
+    create_board_with_kind(
+        width,
+        height,
+        mines,
+        secondary_mines,
+        MineKind::Secondary,
+        rand,
+    )
+}
+
+fn create_board_with_kind(
+    width: usize,
+    height: usize,
+    mines: usize,
+    special_mines: usize,
+    special_kind: MineKind,
     mut rand: impl FnMut(usize, usize) -> usize,
 ) -> Board {
     let mut points: Vec<Point> = Vec::with_capacity(mines);
@@ -275,19 +693,39 @@ pub fn create_board(
         }
     }
 
+    let mines: Vec<(Point, MineKind)> = points
+        .into_iter()
+        .enumerate()
+        .map(|(i, p)| {
+            if i < special_mines {
+                (p, special_kind.clone())
+            } else {
+                (p, MineKind::Positive)
+            }
+        })
+        .collect();
+    create_board_with_mines(width, height, &mines)
+}
+
+/// A board with mines exactly where `mines` says, e.g. to rebuild one saved
+/// with `Board::mine_layout`.
+pub fn create_board_with_mines(width: usize, height: usize, mines: &[(Point, MineKind)]) -> Board {
     let map = (0..height)
         .map(|y| {
             (0..width)
-                .map(|x| {
-                    if points.contains(&Point::new(x, y)) {
-                        Mine { state: Closed }
-                    } else {
-                        Number {
+                .map(
+                    |x| match mines.iter().find(|(p, _)| *p == Point::new(x, y)) {
+                        Some((_, kind)) => Mine {
+                            state: Closed,
+                            kind: kind.clone(),
+                        },
+                        None => Number {
                             state: Closed,
                             count: 0,
-                        }
-                    }
-                })
+                            secondary_count: 0,
+                        },
+                    },
+                )
                 .collect()
         })
         .collect();
@@ -301,18 +739,27 @@ pub fn numbers_on_board(board: Board) -> Board {
                 .map(|x| {
                     let point = Point::new(x, y);
                     match board.at(&point).unwrap() {
-                        Mine { state } => Mine {
+                        Mine { state, kind } => Mine {
                             state: state.clone(),
+                            kind: kind.clone(),
                         },
-                        Number { count: 0, state } => {
-                            let count = board
+                        Number {
+                            count: 0,
+                            secondary_count: 0,
+                            state,
+                        } => {
+                            let (count, secondary_count) = board
                                 .surrounding_points(&point)
                                 .iter()
-                                .filter(|p| matches!(board.at(p), Some(Mine { .. })))
-                                .count() as i32;
+                                .filter_map(|p| match board.at(p) {
+                                    Some(Mine { kind, .. }) => Some(kind.weights()),
+                                    _ => None,
+                                })
+                                .fold((0, 0), |(a, b), (c, d)| (a + c, b + d));
                             Number {
                                 state: state.clone(),
                                 count,
+                                secondary_count,
                             }
                         }
                         _ => unreachable!(),
@@ -328,6 +775,44 @@ pub fn numbers_on_board(board: Board) -> Board {
     }
 }
 
+/// A board part way through a game, from where its mines are and what state
+/// each cell of `map` is in. The numbers are worked out afresh, and the game
+/// is won, in play or ready to start depending on what's open.
+pub fn create_position(map: Vec<Vec<MapElement>>) -> Board {
+    let map = map
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|element| match element {
+                    Number { state, .. } => Number {
+                        state,
+                        count: 0,
+                        secondary_count: 0,
+                    },
+                    mine => mine,
+                })
+                .collect()
+        })
+        .collect();
+    let board = numbers_on_board(Board::new(map));
+    let missing_points = board
+        .points()
+        .filter(|p| matches!(board.at(p), Some(Number { state, .. }) if *state != Open))
+        .count() as i32;
+    let opened = board
+        .points()
+        .any(|p| *board.at(&p).unwrap().state() != Closed);
+    Board {
+        missing_points,
+        state: match (missing_points, opened) {
+            (0, _) => BoardState::Won,
+            (_, true) => BoardState::Playing,
+            (_, false) => BoardState::Ready,
+        },
+        ..board
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -354,7 +839,11 @@ pub mod tests {
             match state {
                 b'O' => Open,
                 b'C' => Closed,
-                b'F' => Flagged,
+                b'F' => Flagged(MineKind::Positive),
+                b'N' => Flagged(MineKind::Negative),
+                b'S' => Flagged(MineKind::Secondary),
+                b'Q' => Questioned,
+                b'E' => Exploded,
                 _ => unreachable!(),
             }
         }
@@ -364,7 +853,7 @@ pub mod tests {
         (c as i32) - (b'0' as i32)
     }
 
-    fn make_map(map: &str, state: &str) -> Vec<Vec<MapElement>> {
+    pub fn make_map(map: &str, state: &str) -> Vec<Vec<MapElement>> {
         map.lines()
             .zip(state.lines())
             .map(|(map_row, state_row)| {
@@ -377,10 +866,20 @@ pub mod tests {
                     .map(|(row_el, state_el)| match row_el {
                         b'X' => Mine {
                             state: state_el.into(),
+                            kind: MineKind::Positive,
+                        },
+                        b'N' => Mine {
+                            state: state_el.into(),
+                            kind: MineKind::Negative,
+                        },
+                        b'Y' => Mine {
+                            state: state_el.into(),
+                            kind: MineKind::Secondary,
                         },
                         _ => Number {
                             state: state_el.into(),
                             count: count_from_bytes(*row_el),
+                            secondary_count: 0,
                         },
                     })
                     .collect()
@@ -403,23 +902,36 @@ pub mod tests {
                 Number {
                     count: 0,
                     state: Open,
+                    secondary_count: 0,
                 },
                 Number {
                     count: 0,
                     state: Closed,
+                    secondary_count: 0,
                 },
             ],
             vec![
                 Number {
                     count: 2,
-                    state: Flagged,
+                    state: Flagged(MineKind::Positive),
+                    secondary_count: 0,
                 },
                 Number {
                     count: 2,
                     state: Closed,
+                    secondary_count: 0,
+                },
+            ],
+            vec![
+                Mine {
+                    state: Closed,
+                    kind: MineKind::Positive,
+                },
+                Mine {
+                    state: Flagged(MineKind::Positive),
+                    kind: MineKind::Positive,
                 },
             ],
-            vec![Mine { state: Closed }, Mine { state: Flagged }],
         ];
 
         assert_eq!(map, expected_map);
@@ -578,6 +1090,304 @@ pub mod tests {
         assert_eq!(board.state, BoardState::Playing);
     }
 
+    #[test]
+    fn test_flagging_cycles_through_question_mark() {
+        let board = numbers_on_board(five_by_two_board()).with_question_marks(true);
+        let board = board.flag_item(&Point::new(3, 1));
+        let board = board.flag_item(&Point::new(3, 1));
+        board_matches!(
+            board,
+            "•••••
+             •••?•"
+        );
+        let board = board.flag_item(&Point::new(3, 1));
+        board_matches!(
+            board,
+            "•••••
+             •••••"
+        );
+    }
+
+    #[test]
+    fn test_opening_question_mark() {
+        let board = numbers_on_board(Board::new(map!(
+            "X0000
+             0X000",
+            "CCCCC
+             CCQCC"
+        )));
+        let board = board.cascade_open_item(&Point::new(4, 1)).unwrap();
+        board_matches!(
+            board,
+            "••1__
+             ••1__"
+        );
+        let board = board.cascade_open_item(&Point::new(1, 0)).unwrap();
+        let board = board.cascade_open_item(&Point::new(0, 1)).unwrap();
+        assert_eq!(board.state, BoardState::Won);
+    }
+
+    #[test]
+    fn test_flagging_numbers_does_not_win() {
+        let board = numbers_on_board(five_by_two_board());
+        let board = (0..5).fold(board, |b, x| b.flag_item(&Point::new(x, 1)));
+        let board = (1..5).fold(board, |b, x| b.flag_item(&Point::new(x, 0)));
+        assert_eq!(board.state, BoardState::Playing);
+    }
+
+    #[test]
+    fn test_robot_flags_question_marks() {
+        let board = numbers_on_board(Board::new(map!(
+            "X0000
+             0X000",
+            "QOOCC
+             OCOCC"
+        )))
+        .with_question_marks(true);
+        let board = board.run_robot_on_point(Point::new(1, 0)).unwrap();
+        board_matches!(
+            board,
+            "F21••
+             2•1••"
+        );
+    }
+
+    #[test]
+    fn test_failing_reveals_the_board() {
+        let board = numbers_on_board(Board::new(map!(
+            "X000N
+             0X000",
+            "FCOFC
+             CCFCN"
+        )));
+        let board = board.cascade_open_item(&Point::new(1, 1)).unwrap();
+        assert_eq!(board.state, BoardState::Failed);
+        board_matches!(
+            board,
+            "F•1WX
+             •*W•W"
+        );
+    }
+
+    #[test]
+    fn test_finished_boards_cannot_be_played() {
+        let board = numbers_on_board(five_by_two_board());
+        let board = board.cascade_open_item(&Point::new(0, 0)).unwrap();
+        assert!(board.cascade_open_item(&Point::new(3, 1)).is_none());
+        assert_eq!(board.flag_item(&Point::new(3, 1)), board);
+    }
+
+    #[test]
+    fn test_chord_item() {
+        let board = numbers_on_board(Board::new(map!(
+            "X0000
+             0X000",
+            "COOCC
+             CFCCC"
+        )));
+        assert!(board.chord_item(&Point::new(1, 0)).is_none());
+        let board = board.chord_item(&Point::new(2, 0)).unwrap();
+        board_matches!(
+            board,
+            "•21__
+             •F1__"
+        );
+        assert!(board.chord_item(&Point::new(2, 0)).is_none());
+    }
+
+    #[test]
+    fn test_chord_item_on_wrong_flag_fails() {
+        let board = numbers_on_board(Board::new(map!(
+            "X0000
+             0X000",
+            "COOCC
+             CCFCC"
+        )));
+        let board = board.chord_item(&Point::new(2, 0)).unwrap();
+        assert_eq!(board.state, BoardState::Failed);
+    }
+
+    #[test]
+    fn test_apply_without_changes() {
+        let board = numbers_on_board(five_by_two_board());
+        let board = board.apply(&Action::Open(Point::new(2, 0))).unwrap();
+        assert!(board.apply(&Action::Open(Point::new(2, 0))).is_none());
+        assert!(board.apply(&Action::Flag(Point::new(2, 0))).is_none());
+        assert!(board.apply(&Action::Chord(Point::new(2, 0))).is_none());
+        assert!(board.apply(&Action::Flag(Point::new(0, 0))).is_some());
+    }
+
+    #[test]
+    fn test_hitting_a_mine_costs_a_life() {
+        let board = numbers_on_board(five_by_two_board()).with_lives(1);
+        let board = board.cascade_open_item(&Point::new(0, 0)).unwrap();
+        board_matches!(
+            board,
+            "*••••
+             •••••"
+        );
+        assert_eq!(board.state, BoardState::Playing);
+        assert_eq!(board.lives, Some(0));
+        assert!(board.cascade_open_item(&Point::new(0, 0)).is_none());
+        let board = board.cascade_open_item(&Point::new(1, 1)).unwrap();
+        assert_eq!(board.state, BoardState::Failed);
+    }
+
+    #[test]
+    fn test_winning_after_hitting_a_mine() {
+        let board = numbers_on_board(five_by_two_board()).with_lives(3);
+        let board = board.cascade_open_item(&Point::new(0, 0)).unwrap();
+        let board = board.cascade_open_item(&Point::new(3, 1)).unwrap();
+        let board = board.cascade_open_item(&Point::new(0, 1)).unwrap();
+        let board = board.cascade_open_item(&Point::new(1, 0)).unwrap();
+        assert_eq!(board.state, BoardState::Won);
+        assert_eq!(board.lives, Some(2));
+    }
+
+    #[test]
+    fn test_robot_counts_exploded_mines() {
+        let board = numbers_on_board(Board::new(map!(
+            "X0000
+             0X000",
+            "EOOCC
+             OCOCC"
+        )));
+        let board = board.run_robot_on_point(Point::new(1, 0)).unwrap();
+        board_matches!(
+            board,
+            "*21••
+             2F1••"
+        );
+    }
+
+    pub fn negative_board() -> Board {
+        Board::new(map!(
+            "X0N00
+             00000",
+            "CCCCC
+             CCCCC"
+        ))
+    }
+
+    #[test]
+    fn test_numbers_on_board_with_negative_mines() {
+        let board = numbers_on_board(negative_board());
+        assert_eq!(board.variant, Variant::NegativeMines);
+        let counts: Vec<_> = (0..5)
+            .map(|x| match board.at(&Point::new(x, 1)) {
+                Some(Number { count, .. }) => *count,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(counts, vec![1, 0, -1, -1, 0]);
+    }
+
+    #[test]
+    fn test_cascade_stops_at_net_zero_numbers() {
+        let board = numbers_on_board(negative_board());
+        let board = board.cascade_open_item(&Point::new(1, 1)).unwrap();
+        board_matches!(
+            board,
+            "•••••
+             •0•••"
+        );
+        let board = board.cascade_open_item(&Point::new(4, 0)).unwrap();
+        board_matches!(
+            board,
+            "•••-1_
+             •0•-1_"
+        );
+    }
+
+    #[test]
+    fn test_flagging_cycles_through_negative_flags() {
+        let board = numbers_on_board(negative_board());
+        let board = board.flag_item(&Point::new(2, 0));
+        board_matches!(
+            board,
+            "••F••
+             •••••"
+        );
+        let board = board.flag_item(&Point::new(2, 0));
+        board_matches!(
+            board,
+            "••N••
+             •••••"
+        );
+        let board = board.flag_item(&Point::new(2, 0));
+        board_matches!(
+            board,
+            "•••••
+             •••••"
+        );
+    }
+
+    #[test]
+    fn test_robot_does_not_play_negative_mines() {
+        let board = numbers_on_board(Board::new(map!(
+            "XXN
+             000",
+            "FCC
+             OOO"
+        )));
+        board_matches!(
+            board,
+            "F••
+             210"
+        );
+        // a classic robot would see the flag satisfying the 1 and open a mine
+        assert!(board.run_robot_on_point(Point::new(1, 1)).is_none());
+    }
+
+    pub fn two_colour_board() -> Board {
+        Board::new(map!(
+            "X00Y
+             0000",
+            "CCCC
+             CCCC"
+        ))
+    }
+
+    #[test]
+    fn test_numbers_on_board_with_two_colours() {
+        let board = numbers_on_board(two_colour_board());
+        assert_eq!(board.variant, Variant::TwoColour);
+        assert_eq!(
+            board.at(&Point::new(2, 1)),
+            Some(&Number {
+                state: Closed,
+                count: 0,
+                secondary_count: 1,
+            })
+        );
+        let board = board.cascade_open_item(&Point::new(0, 1)).unwrap();
+        let board = board.cascade_open_item(&Point::new(1, 1)).unwrap();
+        let board = board.cascade_open_item(&Point::new(2, 0)).unwrap();
+        board_matches!(
+            board,
+            "••01•
+             1010••"
+        );
+    }
+
+    #[test]
+    fn test_flagging_cycles_through_mine_colours() {
+        let board = numbers_on_board(two_colour_board());
+        let board = board.flag_item(&Point::new(3, 0));
+        let board = board.flag_item(&Point::new(3, 0));
+        board_matches!(
+            board,
+            "•••S
+             ••••"
+        );
+        let board = board.flag_item(&Point::new(3, 0));
+        board_matches!(
+            board,
+            "••••
+             ••••"
+        );
+    }
+
     fn board_to_string(board: &Board) -> String {
         let mut ret = String::default();
         for y in 0..board.height {
@@ -586,26 +1396,76 @@ pub mod tests {
                 let el = board.at(&p).unwrap();
                 let v = match el {
                     Mine {
-                        state: MapElementCellState::Flagged,
+                        state: MapElementCellState::Flagged(MineKind::Positive),
+                        ..
                     }
                     | Number {
-                        state: MapElementCellState::Flagged,
+                        state: MapElementCellState::Flagged(MineKind::Positive),
                         ..
                     } => "F".to_string(),
+                    Mine {
+                        state: MapElementCellState::Flagged(MineKind::Negative),
+                        ..
+                    }
+                    | Number {
+                        state: MapElementCellState::Flagged(MineKind::Negative),
+                        ..
+                    } => "N".to_string(),
+                    Mine {
+                        state: MapElementCellState::Flagged(MineKind::Secondary),
+                        ..
+                    }
+                    | Number {
+                        state: MapElementCellState::Flagged(MineKind::Secondary),
+                        ..
+                    } => "S".to_string(),
                     Number {
                         state: MapElementCellState::Closed,
                         ..
                     }
                     | Mine {
                         state: MapElementCellState::Closed,
+                        ..
                     } => "•".to_string(),
+                    Number {
+                        state: MapElementCellState::Questioned,
+                        ..
+                    }
+                    | Mine {
+                        state: MapElementCellState::Questioned,
+                        ..
+                    } => "?".to_string(),
+                    Mine {
+                        state: MapElementCellState::Exploded,
+                        ..
+                    } => "*".to_string(),
+                    Mine {
+                        state: MapElementCellState::Revealed,
+                        ..
+                    } => "X".to_string(),
+                    Mine {
+                        state: MapElementCellState::WronglyFlagged,
+                        ..
+                    }
+                    | Number {
+                        state: MapElementCellState::WronglyFlagged,
+                        ..
+                    } => "W".to_string(),
                     Number {
                         state: MapElementCellState::Open,
-                        count: 0,
-                    } => "_".to_string(),
+                        ..
+                    } if !board.has_surrounding_mines(&p) => "_".to_string(),
+                    Number {
+                        state: MapElementCellState::Open,
+                        count,
+                        secondary_count,
+                    } if board.variant == Variant::TwoColour => {
+                        format!("{}{}", count, secondary_count)
+                    }
                     Number {
                         state: MapElementCellState::Open,
                         count,
+                        ..
                     } => count.to_string(),
                     _ => unreachable!(),
                 };
@@ -1,154 +1,788 @@
 use colored::Colorize;
 use rand::Rng;
+use std::fs;
 use std::io;
+use std::io::IsTerminal;
+use std::path::Path;
 
+mod cli {
+    pub mod animation;
+    pub mod args;
+    pub mod batch;
+    pub mod commands;
+    pub mod coords;
+    pub mod editor;
+    pub mod heatmap;
+    pub mod render;
+    pub mod session;
+    pub mod tui;
+}
+
+use cli::args::Game;
+use cli::args::Options;
+use cli::commands::parse_commands;
+use cli::commands::CommandError;
+use cli::commands::Operation;
+use cli::commands::HELP;
+use cli::coords::column_label;
+use cli::coords::row_label;
+use cli::editor::Edit;
+use cli::editor::Editor;
+use cli::render;
+use cli::render::Style;
+use cli::session::describe;
+use cli::session::Finished;
+use cli::session::Session;
 use lib_minesweeper::create_board;
+use lib_minesweeper::create_negative_mines_board;
+use lib_minesweeper::create_two_colour_board;
+use lib_minesweeper::daily::daily_board;
+use lib_minesweeper::daily::Day;
+use lib_minesweeper::difficulty::BoardSize;
+use lib_minesweeper::infinite::InfiniteBoard;
 use lib_minesweeper::numbers_on_board;
+use lib_minesweeper::replay::Replay;
+use lib_minesweeper::score::score;
+use lib_minesweeper::seeded_rand;
+use lib_minesweeper::stats::FileStorage;
+use lib_minesweeper::stats::Stats;
+use lib_minesweeper::stats::StatsStorage;
+use lib_minesweeper::svg::to_svg;
+use lib_minesweeper::text::from_text;
+use lib_minesweeper::text::to_text;
+use lib_minesweeper::Action;
 use lib_minesweeper::Board;
 use lib_minesweeper::BoardState;
 use lib_minesweeper::MapElement::Mine;
 use lib_minesweeper::MapElement::Number;
 use lib_minesweeper::MapElementCellState::Closed;
-use lib_minesweeper::MapElementCellState::Flagged;
-use lib_minesweeper::MapElementCellState::Open;
+use lib_minesweeper::MapElementCellState::Revealed;
 use lib_minesweeper::Point;
+use lib_minesweeper::Variant;
+
+/// How much of an infinite board is shown at once.
+const VIEWPORT_WIDTH: usize = 30;
+const VIEWPORT_HEIGHT: usize = 16;
 
 fn main() {
-    let width = 8;
-    let height = 8;
-    let mines = 10;
+    let options = match cli::args::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(cli::args::ArgsError::Help) => {
+            println!("{}", cli::args::USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("error: {}\nRun with --help to see the options.", e);
+            std::process::exit(2);
+        }
+    };
+    let seed = options.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let style = options.render.unwrap_or_else(default_style);
+    if style != Style::Colour {
+        colored::control::set_override(false);
+    }
+    match &options.game {
+        Game::Infinite => {
+            play_infinite(seed, style);
+            return;
+        }
+        Game::Batch { games } => {
+            if let Err(e) = cli::batch::run(&options, *games, seed, &mut io::stdout().lock()) {
+                eprintln!("Couldn't write the results: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Game::Edit { path } => {
+            edit(path, &options, style);
+            return;
+        }
+        Game::Analyse { path } => {
+            analyse(path, style);
+            return;
+        }
+        Game::Svg { path } => {
+            print!("{}", to_svg(&read_position(path)));
+            return;
+        }
+        Game::Replay { path } => {
+            // asciicasts are played in terminals, which take colour
+            let style = options.render.unwrap_or(Style::Colour);
+            colored::control::set_override(style == Style::Colour);
+            export_replay(path, &options, style);
+            return;
+        }
+        Game::Classic | Game::Daily => (),
+    }
 
-    let board = create_board(width, height, mines, |x, y| {
-        rand::thread_rng().gen_range(x..y)
+    let daily = if options.game == Game::Daily {
+        Some(Day::today())
+    } else {
+        None
+    };
+    let board = match &daily {
+        Some(day) => {
+            println!("Daily challenge for {}", day);
+            daily_board(day, &options.difficulty.size()).with_question_marks(options.question_marks)
+        }
+        None => new_board(&options, seed),
+    };
+    // daily boards are played again by playing the daily again
+    let seed = if daily.is_none() { Some(seed) } else { None };
+    let tui = !options.prompt
+        && style.is_grid()
+        && io::stdin().is_terminal()
+        && io::stdout().is_terminal();
+    if !tui {
+        play_prompt(board, &options, seed, daily, style);
+        return;
+    }
+    let finished = cli::tui::play(board, style).unwrap_or_else(|e| {
+        eprintln!("Couldn't draw the board: {}", e);
+        std::process::exit(1);
     });
+    if let Some(finished) = finished {
+        print_map(&finished.board, style);
+        report(&finished, seed, daily);
+    }
+}
 
-    let mut board = numbers_on_board(board);
+/// Colour in a terminal, unless `NO_COLOR` asks for none, and plain ASCII
+/// when the output goes to a file or another program.
+fn default_style() -> Style {
+    if !io::stdout().is_terminal() {
+        Style::Ascii
+    } else if std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        Style::Unicode
+    } else {
+        Style::Colour
+    }
+}
+
+/// Tells the player how `finished` went and adds it to their stats, unless
+/// the board came from a file rather than a `seed` or the `daily` challenge.
+fn report(finished: &Finished, seed: Option<u64>, daily: Option<Day>) {
+    let Finished {
+        board,
+        clicks,
+        seconds,
+        assisted,
+    } = finished;
+    if board.state == BoardState::Won {
+        println!("{}", score(board, clicks, *seconds));
+    }
+    if let Some(seed) = seed {
+        println!("Play this board again with --seed {}.", seed);
+    }
+    if seed.is_some() || daily.is_some() {
+        record_stats(board, *seconds, daily, *assisted);
+    }
+}
+
+/// Plays `board` by reading commands a line at a time, until the player
+/// quits or the input ends. New games are made the way `options` say.
+fn play_prompt(
+    board: Board,
+    options: &Options,
+    mut seed: Option<u64>,
+    mut daily: Option<Day>,
+    style: Style,
+) {
+    let mut session = Session::new(board);
+    // each game is only counted once, however often its end is undone
+    let mut reported = false;
+    print_map(&session.board, style);
+    println!(
+        "Type commands like oC5 to open column C, row 5, fB2 to flag B2, or help for the rest."
+    );
 
     loop {
-        colorized_print_map(&board);
-        if matches!(board.state, BoardState::Failed | BoardState::Won) {
-            return;
+        if !reported {
+            if let Some(finished) = session.finished() {
+                report(&finished, seed, daily);
+                reported = true;
+                println!("Type new for another game, undo to take the last move back, or quit.");
+            }
         }
 
-        println!("Please input operation (open or flag), column and row.Examples:\no35 to open column 3, row 5\nf13 to flag column 1, row 3");
         let mut line = String::new();
-        io::stdin()
+        let read = io::stdin()
             .read_line(&mut line)
             .expect("failed to read line");
-        let op = process_line(line, &board);
-        match op {
-            Some(Operation::Open { point }) => {
-                board = board.cascade_open_item(&point).unwrap_or(board)
+        if read == 0 {
+            return;
+        }
+        let operations = match process_line(line, &session.board) {
+            Ok(operations) => operations,
+            Err(e) => {
+                println!("{}", e);
+                continue;
             }
-            Some(Operation::Flag { point }) => board = board.flag_item(&point),
-            _ => continue,
+        };
+        let mut show = false;
+        for operation in operations {
+            match operation {
+                Operation::Open { point } => show |= session.apply(&Action::Open(point)),
+                Operation::Flag { point } => show |= session.apply(&Action::Flag(point)),
+                Operation::Chord { point } => show |= session.apply(&Action::Chord(point)),
+                Operation::Undo if session.undo() => show = true,
+                Operation::Undo => println!("There's nothing to undo."),
+                Operation::Hint => match session.hint() {
+                    Some(next) => println!("Hint: {}.", describe(&next)),
+                    None => println!("The robot has nothing to suggest."),
+                },
+                Operation::Robot => match session.robot() {
+                    Some(next) => {
+                        println!("Robot: {}.", describe(&next));
+                        show = true;
+                    }
+                    None => println!("The robot has nothing to do."),
+                },
+                Operation::Auto => {
+                    let moves = session.auto();
+                    println!("The robot made {} moves.", moves);
+                    show |= moves > 0;
+                }
+                Operation::Save { path } => match fs::write(&path, to_text(&session.board)) {
+                    Ok(()) => println!("Saved the board to {}.", path),
+                    Err(e) => println!("Couldn't save to {}: {}", path, e),
+                },
+                Operation::Export { path } => match fs::write(&path, to_svg(&session.board)) {
+                    Ok(()) => println!("Drew the board in {}.", path),
+                    Err(e) => println!("Couldn't draw the board in {}: {}", path, e),
+                },
+                Operation::Record { path } => match &session.replay {
+                    Some(replay) => {
+                        let json = serde_json::to_string(replay).expect("replays serialize");
+                        match fs::write(&path, json) {
+                            Ok(()) => println!("Recorded the game in {}.", path),
+                            Err(e) => println!("Couldn't record to {}: {}", path, e),
+                        }
+                    }
+                    None => println!("Only games played from the start can be recorded."),
+                },
+                Operation::Load { path } => match load_board(&path) {
+                    Ok(board) => {
+                        reported = board.is_finished();
+                        session = Session::new(board);
+                        seed = None;
+                        daily = None;
+                        show = true;
+                    }
+                    Err(e) => println!("Couldn't load {}: {}", path, e),
+                },
+                Operation::New => {
+                    let next = rand::thread_rng().gen();
+                    session = Session::new(new_board(options, next));
+                    seed = Some(next);
+                    daily = None;
+                    reported = false;
+                    show = true;
+                }
+                Operation::Help => println!("{}", HELP),
+                Operation::Quit => return,
+            }
+        }
+        if show {
+            print_map(&session.board, style);
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub enum Operation {
-    Open { point: Point },
-    Flag { point: Point },
-}
+/// Lets the player put a position together and save it to `path`, starting
+/// from what's there or from an empty board the size `options` ask for.
+fn edit(path: &str, options: &Options, style: Style) {
+    let mut editor = if Path::new(path).exists() {
+        Editor::of(&read_position(path))
+    } else {
+        let size = options.difficulty.size();
+        let mut editor = Editor::new(size.width, size.height);
+        editor.lives = options.lives;
+        editor.question_marks = options.question_marks;
+        editor.fair_guesses = options.fair_guesses;
+        editor
+    };
+    // quitting with changes that aren't saved takes asking twice
+    let mut saved = true;
+    print_editor(&editor.board(), style);
+    println!("Type mine B3 to place a mine, open B3 to open a cell, or help for the rest.");
 
-fn process_line(line: String, board: &Board) -> Option<Operation> {
-    let bytes = line.as_bytes();
-    match bytes {
-        [op, x, y, b'\n'] => {
-            let x = coord_reverse_mapping(*x);
-            let y = coord_reverse_mapping(*y);
-            let p = Point { x, y };
-            if matches!(board.at(&p), Some(_)) {
-                match op {
-                    b'o' => Some(Operation::Open { point: p }),
-                    b'f' => Some(Operation::Flag { point: p }),
-                    _ => None,
+    loop {
+        let mut line = String::new();
+        let read = io::stdin()
+            .read_line(&mut line)
+            .expect("failed to read line");
+        if read == 0 {
+            return;
+        }
+        let board = editor.board();
+        let edits = match cli::editor::parse_edits(&line, board.width, board.height) {
+            Ok(edits) => edits,
+            Err(e) => {
+                println!("{}", e);
+                continue;
+            }
+        };
+        let mut show = false;
+        for edit in edits {
+            match edit {
+                Edit::Save => match fs::write(path, to_text(&editor.board())) {
+                    Ok(()) => {
+                        println!("Saved the board to {}.", path);
+                        saved = true;
+                    }
+                    Err(e) => println!("Couldn't save to {}: {}", path, e),
+                },
+                Edit::Export { path } => match fs::write(&path, to_svg(&editor.board())) {
+                    Ok(()) => println!("Drew the board in {}.", path),
+                    Err(e) => println!("Couldn't draw the board in {}: {}", path, e),
+                },
+                Edit::Help => println!("{}", cli::editor::HELP),
+                Edit::Quit if saved => return,
+                Edit::Quit => {
+                    println!("There are changes that aren't saved, quit again to drop them.");
+                    saved = true;
                 }
-            } else {
-                None
+                edit => match editor.apply(&edit) {
+                    Ok(()) => {
+                        saved = false;
+                        show = true;
+                    }
+                    Err(e) => println!("{}", e),
+                },
             }
         }
-        _ => None,
+        if show {
+            print_editor(&editor.board(), style);
+        }
     }
 }
 
-fn coord_reverse_mapping(c: u8) -> i32 {
-    let mut mapping = vec![];
-    mapping.extend(b'0'..=b'9');
-    mapping.extend(b'a'..=b'z');
+/// The board saved in the text format at `path`.
+fn load_board(path: &str) -> Result<Board, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    from_text(&text).map_err(|e| e.to_string())
+}
 
-    mapping
-        .iter()
-        .enumerate()
-        .map(|(i, c)| (i as i32, c))
-        .find(|(_, &x)| c == x)
-        .map(|(i, _)| i)
-        .unwrap_or(-1)
+/// The position saved at `path`, exiting if it can't be read.
+fn read_position(path: &str) -> Board {
+    match load_board(path) {
+        Ok(board) => board,
+        Err(e) => {
+            eprintln!("Couldn't read {}: {}", path, e);
+            std::process::exit(1);
+        }
+    }
 }
 
-fn print_board_state(board: &Board) {
-    print!("Board is currently ");
-    match board.state {
-        BoardState::Won => print!("{}", "🎉🎉  WON! 🎉🎉".green()),
-        BoardState::Playing => print!("{}", "in play".green()),
-        BoardState::Failed => print!("{}", "☠️  FAILED ☠️".red()),
-        _ => unreachable!(),
-    }
-    println!();
-}
-
-fn colorized_print_map(board: &Board) {
-    print_board_state(board);
-    let mut mapping = vec![];
-    mapping.extend((b'0'..=b'9').map(char::from));
-    mapping.extend((b'a'..=b'z').map(char::from));
-    print!("  ");
-    for item in mapping.iter().take(board.width) {
-        print!("{} ", item);
-    }
-    println!();
-    let is_done = matches!(board.state, BoardState::Failed | BoardState::Won);
-    for y in 0..board.height {
-        print!("{} ", mapping[y]);
-        for x in 0..board.width {
-            let x = x as i32;
-            let y = y as i32;
-            let c = match board.at(&Point { x, y }) {
-                Some(Mine { state }) => match (state, is_done) {
-                    (_, true) | (Open, _) => " ".on_red(),
-                    (Flagged, _) => " ".on_bright_green(),
-                    (Closed, _) => " ".on_yellow(),
-                },
-                Some(Number { state, count: 0 }) => match (state, is_done) {
-                    (_, true) | (Open, _) => " ".on_bright_white(),
-                    (Flagged, _) => " ".on_bright_green(),
-                    (Closed, _) => " ".on_yellow(),
-                },
-                Some(Number { state, count }) => match (state, is_done) {
-                    (_, true) | (Open, _) => format!("{}", count).black().on_bright_cyan(),
-                    (Flagged, _) => " ".on_bright_green(),
-                    (Closed, _) => " ".on_yellow(),
+/// Writes the game recorded at `path` as the animations `options` ask for.
+fn export_replay(path: &str, options: &Options, style: Style) {
+    let frames = match fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| serde_json::from_str::<Replay>(&text).map_err(|e| e.to_string()))
+        .and_then(|replay| replay.frames().map_err(|e| e.to_string()))
+    {
+        Ok(frames) => frames,
+        Err(e) => {
+            eprintln!("Couldn't read {}: {}", path, e);
+            std::process::exit(1);
+        }
+    };
+    let write = |out: &str, animation: &dyn Fn(&mut io::BufWriter<fs::File>) -> io::Result<()>| {
+        let written =
+            fs::File::create(out).and_then(|file| animation(&mut io::BufWriter::new(file)));
+        match written {
+            Ok(()) => println!("Wrote {} frames to {}.", frames.len(), out),
+            Err(e) => {
+                eprintln!("Couldn't write {}: {}", out, e);
+                std::process::exit(1);
+            }
+        }
+    };
+    if let Some(out) = &options.gif {
+        write(out, &|file| cli::animation::gif(&frames, file));
+    }
+    if let Some(out) = &options.cast {
+        write(out, &|file| cli::animation::asciicast(&frames, style, file));
+    }
+}
+
+/// Prints the chance of each closed cell of the position saved at `path`
+/// being a mine, with the cells that are certain either way.
+fn analyse(path: &str, style: Style) {
+    let board = read_position(path);
+    let Some(heatmap) = cli::heatmap::Heatmap::of(&board) else {
+        eprintln!("Only classic boards whose numbers add up can be analysed.");
+        std::process::exit(1);
+    };
+    println!("Chance of a mine in each cell, in percent. S is safe and M a mine for sure.");
+    print_grid(
+        board.width,
+        board.height,
+        style,
+        cli::heatmap::CELL_WIDTH,
+        |x, y| heatmap.cell(style, &Point::new(x, y)),
+    );
+    for line in heatmap.summary() {
+        println!("{}", line);
+    }
+}
+
+/// Shows a board being edited, with its mines in sight.
+fn print_editor(board: &Board, style: Style) {
+    println!(
+        "{} by {} with {} {}",
+        board.width,
+        board.height,
+        board.mines,
+        if board.mines == 1 { "mine" } else { "mines" }
+    );
+    print_grid(
+        board.width,
+        board.height,
+        style,
+        render::cell_width(style, &board.variant),
+        |x, y| {
+            let p = Point::new(x, y);
+            let element = match board.at(&p).unwrap() {
+                Mine {
+                    state: Closed,
+                    kind,
+                } => Mine {
+                    state: Revealed,
+                    kind: kind.clone(),
                 },
-                _ => unreachable!(),
+                element => element.clone(),
             };
-            print!("{} ", c);
+            render::cell(
+                style,
+                &element,
+                board.has_surrounding_mines(&p),
+                &board.variant,
+                false,
+            )
+        },
+    );
+}
+
+/// The board `options` ask for, with mines placed from `seed`.
+fn new_board(options: &Options, seed: u64) -> Board {
+    let size = options.difficulty.size();
+    let rand = seeded_rand(seed);
+    // a third of the mines are of the variant's special kind
+    let special = (size.mines / 3).max(1);
+    let board = match options.variant {
+        Variant::Classic => create_board(size.width, size.height, size.mines, rand),
+        Variant::NegativeMines => {
+            create_negative_mines_board(size.width, size.height, size.mines, special, rand)
+        }
+        Variant::TwoColour => {
+            create_two_colour_board(size.width, size.height, size.mines, special, rand)
+        }
+    };
+    let board = numbers_on_board(board)
+        .with_question_marks(options.question_marks)
+        .with_fair_guesses(options.fair_guesses);
+    match options.lives {
+        Some(lives) => board.with_lives(lives),
+        None => board,
+    }
+}
+
+/// Score attack on a board without edges, looking at it through a viewport
+/// that can be scrolled around.
+fn play_infinite(seed: u64, style: Style) {
+    let mut board = InfiniteBoard::new(seed);
+    // the origin is always safe, so start with it in the middle
+    let mut origin = Point {
+        x: -(VIEWPORT_WIDTH as i32) / 2,
+        y: -(VIEWPORT_HEIGHT as i32) / 2,
+    };
+    loop {
+        print_viewport(&board, &origin, style);
+        if board.is_finished() {
+            println!(
+                "You opened {} cells. Play this board again with seed {}.",
+                board.opened, board.seed
+            );
+            return;
+        }
+
+        println!("Please input operation (open, flag or chord), column and row, or w, a, s or d to scroll. Examples:\noC5 to open column C, row 5\nd to scroll right");
+        let mut line = String::new();
+        let read = io::stdin()
+            .read_line(&mut line)
+            .expect("failed to read line");
+        if read == 0 {
+            return;
+        }
+        let (dx, dy) = match line.trim() {
+            "w" => (0, -1),
+            "a" => (-1, 0),
+            "s" => (0, 1),
+            "d" => (1, 0),
+            _ => (0, 0),
+        };
+        if (dx, dy) != (0, 0) {
+            origin.x += dx * VIEWPORT_WIDTH as i32 / 2;
+            origin.y += dy * VIEWPORT_HEIGHT as i32 / 2;
+            continue;
+        }
+        let operations = match parse_commands(&line, VIEWPORT_WIDTH, VIEWPORT_HEIGHT) {
+            Ok(operations) => operations,
+            Err(e) => {
+                println!("{}", e);
+                continue;
+            }
+        };
+        let on_board = |p: Point| Point {
+            x: origin.x + p.x,
+            y: origin.y + p.y,
+        };
+        for operation in operations {
+            let action = match operation {
+                Operation::Open { point } => Action::Open(on_board(point)),
+                Operation::Flag { point } => Action::Flag(on_board(point)),
+                Operation::Chord { point } => Action::Chord(on_board(point)),
+                Operation::Help => {
+                    println!("{}", HELP);
+                    continue;
+                }
+                Operation::Quit => return,
+                _ => {
+                    println!("Only open, flag, chord, help and quit work on an infinite board.");
+                    continue;
+                }
+            };
+            board = board.apply(&action).unwrap_or(board);
+        }
+    }
+}
+
+fn load_stats() -> Option<(FileStorage, Stats)> {
+    let storage = FileStorage {
+        path: dirs::config_dir()?.join("minesweeper").join("stats.json"),
+    };
+    match storage.load() {
+        Ok(stats) => Some((storage, stats)),
+        Err(e) => {
+            eprintln!("Couldn't read stats from {}: {}", storage.path.display(), e);
+            None
+        }
+    }
+}
+
+/// Adds the finished game on `board` to the saved stats and prints them.
+/// Only the first daily challenge of each day counts.
+/// Games the robot helped with aren't rated and don't set best times.
+fn record_stats(board: &Board, seconds: f64, daily: Option<Day>, assisted: bool) {
+    let (mut storage, mut stats) = match load_stats() {
+        Some(loaded) => loaded,
+        None => return,
+    };
+    let size = BoardSize::of(board);
+    if let Some(day) = daily {
+        if stats.played_daily(&size, &day) {
+            println!(
+                "You already played the daily challenge for {}, so this one doesn't count.",
+                day
+            );
+            return;
+        }
+        stats.record_daily(size, day);
+    }
+    if stats.record_game(board, assisted, seconds) {
+        println!("{}", "New personal best!".green());
+    }
+    if !assisted {
+        if let Err(e) = stats.rate_game(board, seconds) {
+            println!("Not rated: {}.", e);
+        }
+    }
+    if let Some(record) = stats.get(&size) {
+        println!("{}: {} · {}", size, record, stats.rating());
+    }
+    if let Err(e) = storage.save(&stats) {
+        eprintln!("Couldn't save stats to {}: {}", storage.path.display(), e);
+    }
+}
+
+fn process_line(line: String, board: &Board) -> Result<Vec<Operation>, CommandError> {
+    parse_commands(&line, board.width, board.height)
+}
+
+fn board_state(board: &Board, style: Style) -> String {
+    let colour = style == Style::Colour;
+    let mut line = format!(
+        "Board is currently {}",
+        match board.state {
+            BoardState::Won if colour => "🎉🎉  WON! 🎉🎉".green().to_string(),
+            BoardState::Won => "won".to_string(),
+            BoardState::Ready | BoardState::Playing => "in play".green().to_string(),
+            BoardState::Failed if colour => "☠️  FAILED ☠️".red().to_string(),
+            BoardState::Failed => "failed".to_string(),
+            _ => unreachable!(),
         }
-        print!("{}", mapping[y]);
-        println!();
+    );
+    if let Some(lives) = board.lives {
+        line += &format!(
+            " with {} {} left",
+            lives,
+            if lives == 1 { "life" } else { "lives" }
+        );
+    }
+    line
+}
+
+fn print_map(board: &Board, style: Style) {
+    println!("{}", board_state(board, style));
+    for line in map_lines(board, style) {
+        println!("{}", line);
     }
+}
+
+/// The lines `board` is drawn with, without its state.
+fn map_lines(board: &Board, style: Style) -> Vec<String> {
+    // lost boards come with their own post-mortem, won ones are shown in full
+    let is_done = board.state == BoardState::Won;
+    grid_lines(
+        board.width,
+        board.height,
+        style,
+        render::cell_width(style, &board.variant),
+        |x, y| {
+            let p = Point::new(x, y);
+            let element = board.at(&p).unwrap();
+            let has_surrounding_mines = board.has_surrounding_mines(&p);
+            render::cell(
+                style,
+                element,
+                has_surrounding_mines,
+                &board.variant,
+                is_done,
+            )
+        },
+    )
+}
+
+/// Prints `width` by `height` cells `cell_width` wide, drawing each with
+/// `cell`, with the labels around them. Screen readers get a sentence for each row
+/// instead.
+fn print_grid(
+    width: usize,
+    height: usize,
+    style: Style,
+    cell_width: usize,
+    cell: impl Fn(usize, usize) -> String,
+) {
+    for line in grid_lines(width, height, style, cell_width, cell) {
+        println!("{}", line);
+    }
+}
+
+/// The lines `print_grid` prints.
+fn grid_lines(
+    width: usize,
+    height: usize,
+    style: Style,
+    cell_width: usize,
+    cell: impl Fn(usize, usize) -> String,
+) -> Vec<String> {
+    if !style.is_grid() {
+        return (0..height)
+            .map(|y| {
+                let words: Vec<String> = (0..width).map(|x| cell(x, y)).collect();
+                render::describe_row(y, &words)
+            })
+            .collect();
+    }
+    let label_width = row_label(height - 1).len();
+    let labels = column_label_lines(width, cell_width, label_width);
+    let mut lines = labels.clone();
+    for y in 0..height {
+        let label = row_label(y);
+        let mut line = format!("{:>1$} ", label, label_width);
+        for x in 0..width {
+            line += &format!("{} ", cell(x, y));
+        }
+        line += &label;
+        lines.push(line);
+    }
+    lines.extend(labels);
+    lines
+}
+
+/// Labels for `width` columns of `cell_width` characters after a margin of
+/// `indent`. Labels longer than a column go down over several lines.
+fn column_label_lines(width: usize, cell_width: usize, indent: usize) -> Vec<String> {
+    let labels: Vec<Vec<char>> = (0..width)
+        .map(|x| column_label(x).chars().collect())
+        .collect();
+    let lines = labels.iter().map(|label| label.len()).max().unwrap_or(0);
+    (0..lines)
+        .map(|line| {
+            let mut s = format!("{:1$} ", "", indent);
+            for label in &labels {
+                // right aligned, so the last letters line up
+                let c = (line + label.len())
+                    .checked_sub(lines)
+                    .map_or(' ', |i| label[i]);
+                s += &format!("{:1$} ", c, cell_width);
+            }
+            s
+        })
+        .collect()
+}
 
-    print!("  ");
-    for item in mapping.iter().take(board.width) {
-        print!("{} ", item);
+/// Shows the part of an infinite `board` with `origin` in the top left
+/// corner, labelled relative to it.
+fn print_viewport(board: &InfiniteBoard, origin: &Point, style: Style) {
+    print!("Board is currently ");
+    match board.state {
+        BoardState::Failed if style == Style::Colour => print!("{}", "☠️  FAILED ☠️".red()),
+        BoardState::Failed => print!("failed"),
+        _ => print!("{}", "in play".green()),
     }
-    println!();
+    println!(
+        " with {} cells opened, showing from ({}, {})",
+        board.opened, origin.x, origin.y
+    );
+    let cells = board.region(origin, VIEWPORT_WIDTH, VIEWPORT_HEIGHT);
+    print_grid(
+        VIEWPORT_WIDTH,
+        VIEWPORT_HEIGHT,
+        style,
+        render::cell_width(style, &Variant::Classic),
+        |x, y| {
+            let element = match cells[y][x].clone() {
+                // mines in sight are shown once the game is lost
+                Mine {
+                    state: Closed,
+                    kind,
+                } if board.is_finished() => Mine {
+                    state: Revealed,
+                    kind,
+                },
+                element => element,
+            };
+            let has_surrounding_mines = matches!(element, Number { count, .. } if count != 0);
+            render::cell(
+                style,
+                &element,
+                has_surrounding_mines,
+                &Variant::Classic,
+                false,
+            )
+        },
+    );
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use lib_minesweeper::*;
+    use lib_minesweeper::MapElement;
+    use lib_minesweeper::MapElementCellState::Open;
+    use lib_minesweeper::MineKind;
     use pretty_assertions::assert_eq;
 
     // TODO: I'm a dummy and couldn't figure out how to import this function and the next one from lib.rs
@@ -159,10 +793,12 @@ mod tests {
                     .map(|(open, count)| match count {
                         -1 => Mine {
                             state: if *open { Open } else { Closed },
+                            kind: MineKind::Positive,
                         },
                         count => Number {
                             state: if *open { Open } else { Closed },
                             count: *count,
+                            secondary_count: 0,
                         },
                     })
                     .collect()
@@ -179,24 +815,47 @@ mod tests {
 
     #[test]
     fn test_process_line() {
-        let o = process_line(String::from("o01\n"), &tests::five_by_two_board());
+        let o = process_line(String::from("oA2\n"), &tests::five_by_two_board());
         assert_eq!(
             o,
-            Some(Operation::Open {
+            Ok(vec![Operation::Open {
                 point: Point { x: 0, y: 1 }
-            })
+            }])
+        );
+    }
+
+    #[test]
+    fn test_process_line_chord() {
+        let o = process_line(String::from("c 3,1\n"), &tests::five_by_two_board());
+        assert_eq!(
+            o,
+            Ok(vec![Operation::Chord {
+                point: Point { x: 2, y: 0 }
+            }])
         );
     }
 
     #[test]
     fn test_process_line_out_of_bounds_argument() {
-        let o = process_line(String::from("o34\n"), &tests::five_by_two_board());
-        assert_eq!(o, None);
+        let o = process_line(String::from("oD3\n"), &tests::five_by_two_board());
+        assert!(o.is_err());
+    }
+
+    #[test]
+    fn test_process_line_wide_board() {
+        let board = Board::new(make_map(vec![vec![(false, 0); 40]; 12]));
+        let o = process_line(String::from("fAN12\n"), &board);
+        assert_eq!(
+            o,
+            Ok(vec![Operation::Flag {
+                point: Point { x: 39, y: 11 }
+            }])
+        );
     }
 
     #[test]
     fn test_process_line_bad_arguments() {
         let o = process_line(String::from("o\n"), &tests::five_by_two_board());
-        assert_eq!(o, None);
+        assert!(o.is_err());
     }
 }
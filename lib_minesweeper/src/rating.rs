@@ -0,0 +1,180 @@
+//! Glicko ratings for players, where every game is a match against the
+//! board. Boards get a rating of their own from how hard their layout is,
+//! and beating a hard board quickly is worth more than a slow win on an easy
+//! one.
+
+use std::f64::consts::LN_10;
+use std::fmt;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::analysis::analyse;
+use crate::{Board, BoardState, Variant};
+
+pub const INITIAL_RATING: f64 = 1500.0;
+pub const INITIAL_DEVIATION: f64 = 350.0;
+/// Deviations never shrink below this, so ratings keep moving.
+pub const MIN_DEVIATION: f64 = 30.0;
+/// Winning at this pace counts as a full win, slower wins count for less.
+pub const PAR_THREE_BV_PER_SECOND: f64 = 2.0;
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Rating {
+    pub rating: f64,
+    /// How unsure we still are about `rating`.
+    pub deviation: f64,
+    pub games: u32,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Rating {
+            rating: INITIAL_RATING,
+            deviation: INITIAL_DEVIATION,
+            games: 0,
+        }
+    }
+}
+
+impl fmt::Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "rating {:.0} ± {:.0}", self.rating, self.deviation)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum RatingError {
+    /// Only classic boards are rated, as the analysis counts every mine the
+    /// same.
+    Variant(Variant),
+    /// Extra lives and fair guesses make a board easier than its layout.
+    Lives,
+    FairGuesses,
+}
+
+impl fmt::Display for RatingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RatingError::Variant(variant) => {
+                write!(f, "only classic boards are rated, not {:?} ones", variant)
+            }
+            RatingError::Lives => write!(f, "games with lives aren't rated"),
+            RatingError::FairGuesses => write!(f, "games with fair guesses aren't rated"),
+        }
+    }
+}
+
+impl std::error::Error for RatingError {}
+
+/// How hard the layout of `board` is, on the same scale as player ratings.
+/// A beginner board comes out around 1400 and an expert one near 2000.
+///
+/// It only looks at the layout, without playing the board, so it's cheap
+/// enough to work out for every game. Numbers that don't border an opening
+/// stand in for the guessing a layout takes, as they're where it happens.
+/// Only games with none of the help lives and fair guesses give are rated.
+pub fn board_rating(board: &Board) -> Result<f64, RatingError> {
+    if board.variant != Variant::Classic {
+        return Err(RatingError::Variant(board.variant.clone()));
+    }
+    if board.lives.is_some() {
+        return Err(RatingError::Lives);
+    }
+    if board.fair_guesses {
+        return Err(RatingError::FairGuesses);
+    }
+    let analysis = analyse(board);
+    let three_bv = analysis.three_bv.max(1) as f64;
+    Ok(700.0
+        + 150.0 * three_bv.ln()
+        + 1500.0 * analysis.mine_density
+        + 200.0 * analysis.isolated_numbers as f64 / three_bv)
+}
+
+/// How well a finished game went, from 0 for a loss to 1 for a win at par.
+pub fn outcome(board: &Board, seconds: f64) -> f64 {
+    if board.state != BoardState::Won {
+        return 0.0;
+    }
+    let three_bv_per_second = if seconds > 0.0 {
+        analyse(board).three_bv as f64 / seconds
+    } else {
+        PAR_THREE_BV_PER_SECOND
+    };
+    0.5 + 0.5 * (three_bv_per_second / PAR_THREE_BV_PER_SECOND).min(1.0)
+}
+
+const Q: f64 = LN_10 / 400.0;
+
+impl Rating {
+    /// The rating after a match scoring `outcome` against an opponent rated
+    /// `opponent`. Boards' ratings are exact, so Glicko's dampening for the
+    /// opponent's deviation drops out.
+    pub fn update(&self, opponent: f64, outcome: f64) -> Rating {
+        let expected = 1.0 / (1.0 + 10f64.powf(-(self.rating - opponent) / 400.0));
+        let d_squared = 1.0 / (Q * Q * expected * (1.0 - expected));
+        let precision = 1.0 / (self.deviation * self.deviation) + 1.0 / d_squared;
+        Rating {
+            rating: self.rating + Q / precision * (outcome - expected),
+            deviation: (1.0 / precision).sqrt().max(MIN_DEVIATION),
+            games: self.games + 1,
+        }
+    }
+
+    /// The rating after finishing the game on `board` in `seconds`.
+    pub fn rate_game(&self, board: &Board, seconds: f64) -> Result<Rating, RatingError> {
+        Ok(self.update(board_rating(board)?, outcome(board, seconds)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::Difficulty;
+    use crate::{create_board, create_two_colour_board, numbers_on_board, seeded_rand};
+
+    #[test]
+    fn test_update() {
+        let rating = Rating::default();
+        let won = rating.update(1500.0, 1.0);
+        let lost = rating.update(1500.0, 0.0);
+        assert!(won.rating > 1500.0 && lost.rating < 1500.0);
+        assert!((won.rating - 1500.0 - (1500.0 - lost.rating)).abs() < 1e-9);
+        assert!(won.deviation < rating.deviation);
+        assert_eq!(won.games, 1);
+        // beating a stronger opponent is worth more
+        assert!(rating.update(1900.0, 1.0).rating > won.rating);
+    }
+
+    #[test]
+    fn test_board_rating() {
+        // single boards vary a lot with how much guessing they take
+        let rate = |difficulty: Difficulty| -> f64 {
+            let size = difficulty.size();
+            (0..5)
+                .map(|seed| {
+                    let board =
+                        create_board(size.width, size.height, size.mines, seeded_rand(seed));
+                    board_rating(&numbers_on_board(board)).unwrap()
+                })
+                .sum()
+        };
+        assert!(rate(Difficulty::Beginner) < rate(Difficulty::Intermediate));
+        assert!(rate(Difficulty::Intermediate) < rate(Difficulty::Expert));
+
+        let board = numbers_on_board(create_two_colour_board(9, 9, 10, 4, seeded_rand(0)));
+        assert_eq!(
+            board_rating(&board),
+            Err(RatingError::Variant(Variant::TwoColour))
+        );
+        let board = numbers_on_board(create_board(9, 9, 10, seeded_rand(0)));
+        assert_eq!(
+            board_rating(&board.clone().with_lives(3)),
+            Err(RatingError::Lives)
+        );
+        assert_eq!(
+            board_rating(&board.with_fair_guesses(true)),
+            Err(RatingError::FairGuesses)
+        );
+    }
+}
@@ -0,0 +1,547 @@
+use std::fmt;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::difficulty::{BoardSize, Difficulty};
+use crate::score::{score, Clicks};
+use crate::{
+    create_board, create_board_with_mines, numbers_on_board, seeded_rand, Action, Board,
+    BoardState, MineKind, Point,
+};
+
+/// Clicks closer together than this are taken to come from a program.
+pub const MIN_CLICK_INTERVAL_MILLIS: i64 = 20;
+/// Comfortably above the best 3BV/s anyone has managed.
+pub const MAX_THREE_BV_PER_SECOND: f64 = 20.0;
+/// How far the claimed time may be from the one the replay adds up to.
+pub const TIME_TOLERANCE_SECONDS: f64 = 0.5;
+
+/// Enough to rebuild the board a game was played on.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum ReplayBoard {
+    /// Generated by `create_board` with `seeded_rand(seed)`.
+    Seeded { seed: u64, size: BoardSize },
+    Layout {
+        width: usize,
+        height: usize,
+        mines: Vec<(Point, MineKind)>,
+    },
+}
+
+impl ReplayBoard {
+    pub fn of(board: &Board) -> ReplayBoard {
+        ReplayBoard::Layout {
+            width: board.width,
+            height: board.height,
+            mines: board.mine_layout(),
+        }
+    }
+
+    fn build(&self) -> Result<Board, ReplayError> {
+        let board = match self {
+            ReplayBoard::Seeded { seed, size } => {
+                Difficulty::custom(size.width, size.height, size.mines)
+                    .map_err(|e| ReplayError::BadBoard(e.to_string()))?;
+                create_board(size.width, size.height, size.mines, seeded_rand(*seed))
+            }
+            ReplayBoard::Layout {
+                width,
+                height,
+                mines,
+            } => {
+                // like seeded boards, so layouts can't be emptier or fuller
+                // than any board that can be played
+                Difficulty::custom(*width, *height, mines.len())
+                    .map_err(|e| ReplayError::BadBoard(e.to_string()))?;
+                for (i, (p, _)) in mines.iter().enumerate() {
+                    if p.x < 0 || p.x as usize >= *width || p.y < 0 || p.y as usize >= *height {
+                        return Err(ReplayError::BadBoard(format!(
+                            "mine off the board at {:?}",
+                            p
+                        )));
+                    }
+                    if mines[..i].iter().any(|(other, _)| other == p) {
+                        return Err(ReplayError::BadBoard(format!("two mines at {:?}", p)));
+                    }
+                }
+                create_board_with_mines(*width, *height, mines)
+            }
+        };
+        Ok(numbers_on_board(board))
+    }
+}
+
+/// An action and when it happened, in milliseconds since the board was shown.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct TimedAction {
+    pub millis: i64,
+    pub action: Action,
+}
+
+/// Everything needed to play a game again.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub board: ReplayBoard,
+    pub lives: Option<u32>,
+    pub question_marks: bool,
+    #[serde(default)]
+    pub fair_guesses: bool,
+    pub actions: Vec<TimedAction>,
+}
+
+impl Replay {
+    /// An empty replay of a game about to be played on `board`.
+    pub fn new(board: &Board) -> Replay {
+        Replay {
+            board: ReplayBoard::of(board),
+            lives: board.lives,
+            question_marks: board.question_marks,
+            fair_guesses: board.fair_guesses,
+            actions: vec![],
+        }
+    }
+
+    /// An empty replay of a game about to be played on `board`, which was
+    /// made by `create_board` with `seeded_rand(seed)`. Only these can go on
+    /// the leaderboard, as verifying them doesn't take trusting a layout.
+    pub fn seeded(board: &Board, seed: u64) -> Replay {
+        Replay {
+            board: ReplayBoard::Seeded {
+                seed,
+                size: BoardSize::of(board),
+            },
+            ..Replay::new(board)
+        }
+    }
+
+    pub fn record(&mut self, millis: i64, action: Action) {
+        self.actions.push(TimedAction { millis, action });
+    }
+
+    fn build(&self) -> Result<Board, ReplayError> {
+        let board = self
+            .board
+            .build()?
+            .with_question_marks(self.question_marks)
+            .with_fair_guesses(self.fair_guesses);
+        Ok(match self.lives {
+            Some(lives) => board.with_lives(lives),
+            None => board,
+        })
+    }
+
+    /// Time between the first and last actions.
+    pub fn seconds(&self) -> f64 {
+        match (self.actions.first(), self.actions.last()) {
+            (Some(first), Some(last)) => (last.millis - first.millis) as f64 / 1000.0,
+            _ => 0.0,
+        }
+    }
+
+    /// The board as it was shown and after each action that changed it,
+    /// with when that happened, to watch the game again. Actions after the
+    /// end are left out.
+    pub fn frames(&self) -> Result<Vec<(i64, Board)>, ReplayError> {
+        let mut board = self.build()?;
+        let mut frames = vec![(0, board.clone())];
+        for (index, TimedAction { millis, action }) in self.actions.iter().enumerate() {
+            if board.is_finished() {
+                break;
+            }
+            let (Action::Open(p) | Action::Flag(p) | Action::Chord(p)) = action;
+            if board.at(p).is_none() {
+                return Err(ReplayError::OutOfBounds { index });
+            }
+            if let Some(next) = board.apply(action) {
+                board = next;
+                frames.push((*millis, board.clone()));
+            }
+        }
+        Ok(frames)
+    }
+}
+
+/// What a player says happened in a game.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Claim {
+    pub state: BoardState,
+    pub seconds: f64,
+    pub clicks: Clicks,
+}
+
+/// A replay that checked out, and the game it adds up to.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Verified {
+    pub board: Board,
+    pub clicks: Clicks,
+    pub seconds: f64,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ReplayError {
+    BadBoard(String),
+    /// The action at `index` happened before the board was shown.
+    BeforeBoard {
+        index: usize,
+    },
+    /// The action at `index` happened before the one preceding it.
+    OutOfOrder {
+        index: usize,
+    },
+    /// The action at `index` came too soon after the one preceding it.
+    TooFast {
+        index: usize,
+    },
+    OutOfBounds {
+        index: usize,
+    },
+    /// The action at `index` came after the game was over.
+    AfterEnd {
+        index: usize,
+    },
+    NotFinished,
+    ImpossibleSpeed {
+        three_bv_per_second: f64,
+    },
+    WrongOutcome {
+        claimed: BoardState,
+        actual: BoardState,
+    },
+    WrongTime {
+        claimed: f64,
+        actual: f64,
+    },
+    WrongClicks {
+        claimed: Clicks,
+        actual: Clicks,
+    },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::BadBoard(reason) => write!(f, "the board can't be rebuilt: {}", reason),
+            ReplayError::BeforeBoard { index } => {
+                write!(f, "action {} happened before the board existed", index)
+            }
+            ReplayError::OutOfOrder { index } => {
+                write!(f, "action {} happened before the previous one", index)
+            }
+            ReplayError::TooFast { index } => {
+                write!(f, "action {} came too soon after the previous one", index)
+            }
+            ReplayError::OutOfBounds { index } => write!(f, "action {} is off the board", index),
+            ReplayError::AfterEnd { index } => {
+                write!(f, "action {} happened after the game was over", index)
+            }
+            ReplayError::NotFinished => write!(f, "the game isn't over"),
+            ReplayError::ImpossibleSpeed {
+                three_bv_per_second,
+            } => write!(
+                f,
+                "{:.2} 3BV/s is not humanly possible",
+                three_bv_per_second
+            ),
+            ReplayError::WrongOutcome { claimed, actual } => {
+                write!(f, "claimed {:?} but the game was {:?}", claimed, actual)
+            }
+            ReplayError::WrongTime { claimed, actual } => write!(
+                f,
+                "claimed {:.2}s but the game took {:.2}s",
+                claimed, actual
+            ),
+            ReplayError::WrongClicks { claimed, actual } => {
+                write!(f, "claimed {:?} but the game had {:?}", claimed, actual)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+/// Plays `replay` again and checks that it adds up to `claim`.
+pub fn verify(replay: &Replay, claim: &Claim) -> Result<Verified, ReplayError> {
+    let mut board = replay.build()?;
+    let mut clicks = Clicks::default();
+    let mut previous: Option<i64> = None;
+    for (index, TimedAction { millis, action }) in replay.actions.iter().enumerate() {
+        if *millis < 0 {
+            return Err(ReplayError::BeforeBoard { index });
+        }
+        match previous {
+            Some(previous) if *millis < previous => return Err(ReplayError::OutOfOrder { index }),
+            Some(previous) if millis - previous < MIN_CLICK_INTERVAL_MILLIS => {
+                return Err(ReplayError::TooFast { index })
+            }
+            _ => (),
+        }
+        previous = Some(*millis);
+        if board.is_finished() {
+            return Err(ReplayError::AfterEnd { index });
+        }
+        let (Action::Open(p) | Action::Flag(p) | Action::Chord(p)) = action;
+        if board.at(p).is_none() {
+            return Err(ReplayError::OutOfBounds { index });
+        }
+        let next = board.apply(action);
+        clicks.record(action, next.is_some());
+        board = next.unwrap_or(board);
+    }
+
+    if !board.is_finished() {
+        return Err(ReplayError::NotFinished);
+    }
+    if board.state != claim.state {
+        return Err(ReplayError::WrongOutcome {
+            claimed: claim.state.clone(),
+            actual: board.state,
+        });
+    }
+    let seconds = replay.seconds();
+    if (claim.seconds - seconds).abs() > TIME_TOLERANCE_SECONDS {
+        return Err(ReplayError::WrongTime {
+            claimed: claim.seconds,
+            actual: seconds,
+        });
+    }
+    if clicks != claim.clicks {
+        return Err(ReplayError::WrongClicks {
+            claimed: claim.clicks.clone(),
+            actual: clicks,
+        });
+    }
+    if board.state == BoardState::Won {
+        // a single click can win in no time, and more take at least
+        // MIN_CLICK_INTERVAL_MILLIS between them, so the rate is all to check
+        let three_bv_per_second = score(&board, &clicks, seconds).three_bv_per_second;
+        if three_bv_per_second > MAX_THREE_BV_PER_SECOND {
+            return Err(ReplayError::ImpossibleSpeed {
+                three_bv_per_second,
+            });
+        }
+    }
+    Ok(Verified {
+        board,
+        clicks,
+        seconds,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::five_by_two_board;
+    use pretty_assertions::assert_eq;
+
+    fn won_replay() -> Replay {
+        let mut replay = Replay::new(&numbers_on_board(five_by_two_board()));
+        replay.record(1000, Action::Open(Point::new(3, 1)));
+        replay.record(1200, Action::Flag(Point::new(0, 0)));
+        replay.record(1500, Action::Open(Point::new(1, 0)));
+        replay.record(2000, Action::Open(Point::new(0, 1)));
+        replay
+    }
+
+    fn won_claim() -> Claim {
+        Claim {
+            state: BoardState::Won,
+            seconds: 1.0,
+            clicks: Clicks {
+                left: 3,
+                right: 1,
+                chord: 0,
+                wasted: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let verified = verify(&won_replay(), &won_claim()).unwrap();
+        assert_eq!(verified.board.state, BoardState::Won);
+        assert_eq!(verified.seconds, 1.0);
+    }
+
+    #[test]
+    fn test_verify_single_click_win() {
+        let board = numbers_on_board(create_board_with_mines(
+            3,
+            3,
+            &[(Point::new(0, 0), MineKind::Positive)],
+        ));
+        let mut replay = Replay::new(&board);
+        replay.record(700, Action::Open(Point::new(2, 2)));
+        let claim = Claim {
+            state: BoardState::Won,
+            seconds: 0.0,
+            clicks: Clicks {
+                left: 1,
+                ..Clicks::default()
+            },
+        };
+        let verified = verify(&replay, &claim).unwrap();
+        assert_eq!(verified.board.state, BoardState::Won);
+        assert_eq!(verified.seconds, 0.0);
+    }
+
+    #[test]
+    fn test_verify_rejects_wrong_claims() {
+        let replay = won_replay();
+        let claim = Claim {
+            seconds: 0.2,
+            ..won_claim()
+        };
+        assert_eq!(
+            verify(&replay, &claim),
+            Err(ReplayError::WrongTime {
+                claimed: 0.2,
+                actual: 1.0
+            })
+        );
+        let claim = Claim {
+            state: BoardState::Failed,
+            ..won_claim()
+        };
+        assert!(matches!(
+            verify(&replay, &claim),
+            Err(ReplayError::WrongOutcome { .. })
+        ));
+        let claim = Claim {
+            clicks: Clicks::default(),
+            ..won_claim()
+        };
+        assert!(matches!(
+            verify(&replay, &claim),
+            Err(ReplayError::WrongClicks { .. })
+        ));
+    }
+
+    #[test]
+    fn test_verify_rejects_impossible_actions() {
+        let mut replay = won_replay();
+        replay.actions[0].millis = -5;
+        assert_eq!(
+            verify(&replay, &won_claim()),
+            Err(ReplayError::BeforeBoard { index: 0 })
+        );
+
+        let mut replay = won_replay();
+        replay.actions[2].millis = 1100;
+        assert_eq!(
+            verify(&replay, &won_claim()),
+            Err(ReplayError::OutOfOrder { index: 2 })
+        );
+
+        let mut replay = won_replay();
+        replay.actions[2].millis = 1205;
+        assert_eq!(
+            verify(&replay, &won_claim()),
+            Err(ReplayError::TooFast { index: 2 })
+        );
+
+        let mut replay = won_replay();
+        replay.record(2500, Action::Open(Point::new(4, 0)));
+        assert_eq!(
+            verify(&replay, &won_claim()),
+            Err(ReplayError::AfterEnd { index: 4 })
+        );
+
+        let mut replay = won_replay();
+        replay.actions.pop();
+        assert_eq!(verify(&replay, &won_claim()), Err(ReplayError::NotFinished));
+    }
+
+    #[test]
+    fn test_verify_rejects_bad_boards() {
+        let mut replay = won_replay();
+        replay.board = ReplayBoard::Layout {
+            width: 5,
+            height: 2,
+            mines: vec![
+                (Point::new(0, 0), MineKind::Positive),
+                (Point::new(0, 0), MineKind::Positive),
+            ],
+        };
+        assert!(matches!(
+            verify(&replay, &won_claim()),
+            Err(ReplayError::BadBoard(_))
+        ));
+        // without mines every click wins
+        replay.board = ReplayBoard::Layout {
+            width: 5,
+            height: 2,
+            mines: vec![],
+        };
+        assert_eq!(
+            verify(&replay, &won_claim()),
+            Err(ReplayError::BadBoard(
+                "boards need at least one mine".into()
+            ))
+        );
+        replay.board = ReplayBoard::Layout {
+            width: 5,
+            height: 2,
+            mines: vec![(Point::new(5, 0), MineKind::Positive)],
+        };
+        assert!(matches!(
+            verify(&replay, &won_claim()),
+            Err(ReplayError::BadBoard(_))
+        ));
+    }
+
+    #[test]
+    fn test_frames() {
+        let mut replay = won_replay();
+        // opening an open cell changes nothing, and the game is over by the end
+        replay.actions.insert(
+            1,
+            TimedAction {
+                millis: 1100,
+                action: Action::Open(Point::new(3, 1)),
+            },
+        );
+        replay.record(2500, Action::Flag(Point::new(2, 0)));
+        let frames = replay.frames().unwrap();
+        let millis: Vec<i64> = frames.iter().map(|(millis, _)| *millis).collect();
+        assert_eq!(millis, vec![0, 1000, 1200, 1500, 2000]);
+        assert_eq!(frames[0].1.state, BoardState::Ready);
+        assert_eq!(frames[4].1.state, BoardState::Won);
+        replay.actions[0].action = Action::Open(Point::new(9, 0));
+        assert_eq!(replay.frames(), Err(ReplayError::OutOfBounds { index: 0 }));
+    }
+
+    #[test]
+    fn test_verify_seeded() {
+        let size = Difficulty::Beginner.size();
+        let board = numbers_on_board(create_board(
+            size.width,
+            size.height,
+            size.mines,
+            seeded_rand(7),
+        ));
+        let mine = board.mine_layout()[0].0;
+        let replay = Replay {
+            board: ReplayBoard::Seeded { seed: 7, size },
+            lives: None,
+            question_marks: false,
+            fair_guesses: false,
+            actions: vec![TimedAction {
+                millis: 300,
+                action: Action::Open(mine),
+            }],
+        };
+        assert_eq!(Replay::seeded(&board, 7).board, replay.board);
+        let claim = Claim {
+            state: BoardState::Failed,
+            seconds: 0.0,
+            clicks: Clicks {
+                left: 1,
+                ..Clicks::default()
+            },
+        };
+        assert_eq!(
+            verify(&replay, &claim).unwrap().board.state,
+            BoardState::Failed
+        );
+    }
+}
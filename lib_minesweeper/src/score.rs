@@ -0,0 +1,134 @@
+use std::fmt;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::analysis::analyse;
+use crate::{Action, Board};
+
+/// What the player clicked during a game.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Clicks {
+    pub left: u32,
+    pub right: u32,
+    pub chord: u32,
+    /// Clicks of any kind that left the board as it was.
+    pub wasted: u32,
+}
+
+impl Clicks {
+    /// Counts `action`, with `changed` telling whether it did anything.
+    pub fn record(&mut self, action: &Action, changed: bool) {
+        match action {
+            Action::Open(_) => self.left += 1,
+            Action::Flag(_) => self.right += 1,
+            Action::Chord(_) => self.chord += 1,
+        }
+        if !changed {
+            self.wasted += 1;
+        }
+    }
+
+    pub fn total(&self) -> u32 {
+        self.left + self.right + self.chord
+    }
+
+    pub fn effective(&self) -> u32 {
+        self.total() - self.wasted
+    }
+}
+
+/// How well a game was played, as compared by speedrunners.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Score {
+    pub three_bv: usize,
+    pub seconds: f64,
+    pub clicks: Clicks,
+    /// 3BV cleared per second.
+    pub three_bv_per_second: f64,
+    /// Index of efficiency: 3BV over all clicks.
+    pub ioe: f64,
+    /// 3BV over the clicks that did something.
+    pub throughput: f64,
+    /// Share of the clicks that did something.
+    pub correctness: f64,
+}
+
+/// Scores a finished game on `board` that took `seconds`.
+pub fn score(board: &Board, clicks: &Clicks, seconds: f64) -> Score {
+    let three_bv = analyse(board).three_bv;
+    Score {
+        three_bv,
+        seconds,
+        clicks: clicks.clone(),
+        three_bv_per_second: ratio(three_bv as f64, seconds),
+        ioe: ratio(three_bv as f64, clicks.total() as f64),
+        throughput: ratio(three_bv as f64, clicks.effective() as f64),
+        correctness: ratio(clicks.effective() as f64, clicks.total() as f64),
+    }
+}
+
+fn ratio(a: f64, b: f64) -> f64 {
+    if b > 0.0 {
+        a / b
+    } else {
+        0.0
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "3BV {} · 3BV/s {:.2} · IOE {:.2} · throughput {:.2} · correctness {:.2}",
+            self.three_bv, self.three_bv_per_second, self.ioe, self.throughput, self.correctness
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::five_by_two_board;
+    use crate::{numbers_on_board, Point};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_score() {
+        let mut board = numbers_on_board(five_by_two_board());
+        let mut clicks = Clicks::default();
+        for action in [
+            Action::Open(Point::new(3, 1)),
+            Action::Flag(Point::new(0, 0)),
+            Action::Open(Point::new(3, 1)),
+            Action::Open(Point::new(0, 1)),
+            Action::Open(Point::new(1, 0)),
+        ] {
+            let next = board.apply(&action);
+            clicks.record(&action, next.is_some());
+            board = next.unwrap_or(board);
+        }
+        assert_eq!(
+            clicks,
+            Clicks {
+                left: 4,
+                right: 1,
+                chord: 0,
+                wasted: 1,
+            }
+        );
+        let score = score(&board, &clicks, 2.0);
+        assert_eq!(score.three_bv, 3);
+        assert_eq!(score.three_bv_per_second, 1.5);
+        assert_eq!(score.ioe, 0.6);
+        assert_eq!(score.throughput, 0.75);
+        assert_eq!(score.correctness, 0.8);
+    }
+
+    #[test]
+    fn test_score_without_clicks_or_time() {
+        let board = numbers_on_board(five_by_two_board());
+        let score = score(&board, &Clicks::default(), 0.0);
+        assert_eq!(score.three_bv_per_second, 0.0);
+        assert_eq!(score.ioe, 0.0);
+    }
+}
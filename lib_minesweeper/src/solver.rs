@@ -0,0 +1,775 @@
+//! Works out what can be known about a position from what's on screen:
+//! the chance of each closed cell hiding a mine, and from that which move to
+//! make next.
+//!
+//! Open numbers constrain the closed cells around them. Closed cells next to
+//! a number are split into groups that share numbers, every mine arrangement
+//! of each group is enumerated, and the groups are weighed together with the
+//! cells no number touches, so that the total comes out at the board's mine
+//! count. Only classic boards are supported.
+
+use std::collections::HashMap;
+
+use crate::{Action, Board, MapElement, MapElementCellState, Point, Variant};
+
+/// Past this many steps in a single group the position is given up on.
+pub const MAX_SEARCH_STEPS: usize = 2_000_000;
+
+/// Probabilities this close to 0 or 1 are taken as certain.
+const EPSILON: f64 = 1e-9;
+
+/// What the solver suggests doing next.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Move {
+    /// A cell that can't be a mine.
+    Safe(Point),
+    /// A cell that must be a mine and isn't flagged yet.
+    Mine(Point),
+    /// Nothing is certain, this is the least likely cell to be a mine.
+    Guess { point: Point, probability: f64 },
+}
+
+impl Move {
+    pub fn action(&self) -> Action {
+        match self {
+            Move::Safe(p) | Move::Guess { point: p, .. } => Action::Open(*p),
+            Move::Mine(p) => Action::Flag(*p),
+        }
+    }
+}
+
+fn is_unknown(element: &MapElement) -> bool {
+    matches!(
+        element.state(),
+        MapElementCellState::Closed
+            | MapElementCellState::Questioned
+            | MapElementCellState::Flagged(_)
+    )
+}
+
+fn is_known_mine(element: &MapElement) -> bool {
+    matches!(
+        element,
+        MapElement::Mine {
+            state: MapElementCellState::Exploded | MapElementCellState::Revealed,
+            ..
+        }
+    )
+}
+
+/// Mine arrangements of one group of cells, by how many mines they use.
+struct Group {
+    cells: Vec<usize>,
+    /// `solutions[m]` is how many arrangements use `m` mines.
+    solutions: Vec<f64>,
+    /// `tallies[m][i]` is in how many of those `cells[i]` is a mine.
+    tallies: Vec<Vec<f64>>,
+}
+
+/// What the open numbers say about the cells that aren't open.
+struct Position {
+    /// Closed, questioned and flagged cells, in board order.
+    unknown: Vec<Point>,
+    /// How many mines are among the unknown cells.
+    remaining: usize,
+    /// Each open number's unknown neighbours, as indices into `unknown`, and
+    /// how many of them are mines.
+    constraints: Vec<(Vec<usize>, usize)>,
+}
+
+impl Position {
+    fn of(board: &Board) -> Option<Position> {
+        if board.variant != Variant::Classic {
+            return None;
+        }
+        let unknown: Vec<Point> = board
+            .points()
+            .filter(|p| is_unknown(board.at(p).unwrap()))
+            .collect();
+        let index: HashMap<Point, usize> =
+            unknown.iter().enumerate().map(|(i, p)| (*p, i)).collect();
+        let known_mines = board
+            .points()
+            .filter(|p| is_known_mine(board.at(p).unwrap()))
+            .count();
+        let remaining = board.mines.checked_sub(known_mines)?;
+
+        // every open number says how many mines are among its unknown neighbours
+        let mut constraints: Vec<(Vec<usize>, usize)> = vec![];
+        for p in board.points() {
+            if let Some(MapElement::Number {
+                state: MapElementCellState::Open,
+                count,
+                ..
+            }) = board.at(&p)
+            {
+                let around = board.surrounding_points(&p);
+                let cells: Vec<usize> = around
+                    .iter()
+                    .filter_map(|n| index.get(n).copied())
+                    .collect();
+                let known = around
+                    .iter()
+                    .filter(|n| is_known_mine(board.at(n).unwrap()))
+                    .count() as i32;
+                let mines = count - known;
+                if mines < 0 || mines as usize > cells.len() {
+                    return None;
+                }
+                if !cells.is_empty() {
+                    constraints.push((cells, mines as usize));
+                }
+            }
+        }
+        Some(Position {
+            unknown,
+            remaining,
+            constraints,
+        })
+    }
+}
+
+/// The chance of each closed, questioned or flagged cell being a mine, in
+/// board order. `None` for boards that aren't classic, positions that don't
+/// add up, or positions too large to work out.
+pub fn probabilities(board: &Board) -> Option<Vec<(Point, f64)>> {
+    let Position {
+        unknown,
+        remaining,
+        constraints,
+    } = Position::of(board)?;
+
+    let groups = group_cells(unknown.len(), &constraints)
+        .into_iter()
+        .map(|cells| enumerate(cells, &constraints))
+        .collect::<Option<Vec<Group>>>()?;
+    let in_groups: usize = groups.iter().map(|g| g.cells.len()).sum();
+    let interior = unknown.len() - in_groups;
+
+    // how much weight a total of `m` mines in the groups gets from the ways
+    // of putting the rest in the interior, relative to the likeliest total
+    let max_in_groups: usize = groups.iter().map(|g| g.solutions.len() - 1).sum();
+    let log_weights: Vec<Option<f64>> = (0..=max_in_groups)
+        .map(|m| {
+            let rest = remaining.checked_sub(m)?;
+            if rest > interior {
+                None
+            } else {
+                Some(ln_choose(interior, rest))
+            }
+        })
+        .collect();
+    let max_log = log_weights
+        .iter()
+        .flatten()
+        .cloned()
+        .fold(f64::NEG_INFINITY, f64::max);
+    if max_log == f64::NEG_INFINITY {
+        return None;
+    }
+    let weight = |m: usize| -> f64 {
+        match log_weights.get(m) {
+            Some(Some(log)) => (log - max_log).exp(),
+            _ => 0.0,
+        }
+    };
+
+    let all = convolve(groups.iter().map(|g| &g.solutions));
+    let total: f64 = all.iter().enumerate().map(|(m, n)| n * weight(m)).sum();
+    if total <= 0.0 {
+        return None;
+    }
+
+    let mut result = vec![0.0; unknown.len()];
+    for (j, group) in groups.iter().enumerate() {
+        let others = convolve(
+            groups
+                .iter()
+                .enumerate()
+                .filter(|(k, _)| *k != j)
+                .map(|(_, g)| &g.solutions),
+        );
+        for (m, tallies) in group.tallies.iter().enumerate() {
+            let factor: f64 = others
+                .iter()
+                .enumerate()
+                .map(|(o, n)| n * weight(m + o))
+                .sum();
+            for (i, tally) in tallies.iter().enumerate() {
+                result[group.cells[i]] += tally * factor / total;
+            }
+        }
+    }
+    if interior > 0 {
+        let expected: f64 = all
+            .iter()
+            .enumerate()
+            .map(|(m, n)| n * weight(m) * remaining.saturating_sub(m) as f64)
+            .sum::<f64>()
+            / total;
+        let in_group: Vec<bool> = {
+            let mut v = vec![false; unknown.len()];
+            groups
+                .iter()
+                .flat_map(|g| &g.cells)
+                .for_each(|&c| v[c] = true);
+            v
+        };
+        for (c, p) in result.iter_mut().enumerate() {
+            if !in_group[c] {
+                *p = expected / interior as f64;
+            }
+        }
+    }
+
+    Some(
+        unknown
+            .into_iter()
+            .zip(result)
+            .map(|(p, probability)| {
+                let probability = if probability < EPSILON {
+                    0.0
+                } else if probability > 1.0 - EPSILON {
+                    1.0
+                } else {
+                    probability
+                };
+                (p, probability)
+            })
+            .collect(),
+    )
+}
+
+/// Splits the constrained cells into groups that share no constraint.
+fn group_cells(cells: usize, constraints: &[(Vec<usize>, usize)]) -> Vec<Vec<usize>> {
+    let mut parent: Vec<usize> = (0..cells).collect();
+    fn root(parent: &mut [usize], c: usize) -> usize {
+        let mut c = c;
+        while parent[c] != c {
+            parent[c] = parent[parent[c]];
+            c = parent[c];
+        }
+        c
+    }
+    let mut constrained = vec![false; cells];
+    for (members, _) in constraints {
+        for &c in members {
+            constrained[c] = true;
+            let (a, b) = (root(&mut parent, members[0]), root(&mut parent, c));
+            parent[a] = b;
+        }
+    }
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut order = vec![];
+    for c in (0..cells).filter(|c| constrained[*c]) {
+        let r = root(&mut parent, c);
+        if !groups.contains_key(&r) {
+            order.push(r);
+        }
+        groups.entry(r).or_default().push(c);
+    }
+    order
+        .into_iter()
+        .map(|r| groups.remove(&r).unwrap())
+        .collect()
+}
+
+/// Every mine arrangement of `cells` that satisfies the constraints.
+fn enumerate(cells: Vec<usize>, constraints: &[(Vec<usize>, usize)]) -> Option<Group> {
+    let position: HashMap<usize, usize> = cells.iter().enumerate().map(|(i, c)| (*c, i)).collect();
+    let relevant: Vec<(Vec<usize>, usize)> = constraints
+        .iter()
+        .filter(|(members, _)| position.contains_key(&members[0]))
+        .map(|(members, mines)| (members.iter().map(|c| position[c]).collect(), *mines))
+        .collect();
+    // for each cell, the constraints it takes part in
+    let mut touching: Vec<Vec<usize>> = vec![vec![]; cells.len()];
+    for (k, (members, _)) in relevant.iter().enumerate() {
+        for &i in members {
+            touching[i].push(k);
+        }
+    }
+
+    struct Search<'a> {
+        relevant: &'a [(Vec<usize>, usize)],
+        touching: &'a [Vec<usize>],
+        mines_in: Vec<usize>,
+        open_in: Vec<usize>,
+        assignment: Vec<bool>,
+        solutions: Vec<f64>,
+        tallies: Vec<Vec<f64>>,
+        steps: usize,
+    }
+
+    impl Search<'_> {
+        fn fits(&self, i: usize) -> bool {
+            self.touching[i].iter().all(|&k| {
+                let mines = self.relevant[k].1;
+                self.mines_in[k] <= mines && self.mines_in[k] + self.open_in[k] >= mines
+            })
+        }
+
+        fn go(&mut self, i: usize, mines: usize) -> Option<()> {
+            self.steps += 1;
+            if self.steps > MAX_SEARCH_STEPS {
+                return None;
+            }
+            if i == self.assignment.len() {
+                if self.solutions.len() <= mines {
+                    self.solutions.resize(mines + 1, 0.0);
+                    self.tallies
+                        .resize(mines + 1, vec![0.0; self.assignment.len()]);
+                }
+                self.solutions[mines] += 1.0;
+                for (c, is_mine) in self.assignment.iter().enumerate() {
+                    if *is_mine {
+                        self.tallies[mines][c] += 1.0;
+                    }
+                }
+                return Some(());
+            }
+            for is_mine in [false, true] {
+                self.assignment[i] = is_mine;
+                for &k in &self.touching[i] {
+                    self.open_in[k] -= 1;
+                    if is_mine {
+                        self.mines_in[k] += 1;
+                    }
+                }
+                let fits = self.fits(i);
+                let result = if fits {
+                    self.go(i + 1, mines + is_mine as usize)
+                } else {
+                    Some(())
+                };
+                for &k in &self.touching[i] {
+                    self.open_in[k] += 1;
+                    if is_mine {
+                        self.mines_in[k] -= 1;
+                    }
+                }
+                result?;
+            }
+            Some(())
+        }
+    }
+
+    let mut search = Search {
+        relevant: &relevant,
+        touching: &touching,
+        mines_in: vec![0; relevant.len()],
+        open_in: relevant.iter().map(|(members, _)| members.len()).collect(),
+        assignment: vec![false; cells.len()],
+        solutions: vec![],
+        tallies: vec![],
+        steps: 0,
+    };
+    search.go(0, 0)?;
+    if search.solutions.is_empty() {
+        return None;
+    }
+    Some(Group {
+        cells,
+        solutions: search.solutions,
+        tallies: search.tallies,
+    })
+}
+
+/// How many ways each total can be made by adding one count from each list.
+fn convolve<'a>(lists: impl Iterator<Item = &'a Vec<f64>>) -> Vec<f64> {
+    lists.fold(vec![1.0], |acc, list| {
+        let mut result = vec![0.0; acc.len() + list.len() - 1];
+        for (a, x) in acc.iter().enumerate() {
+            for (b, y) in list.iter().enumerate() {
+                result[a + b] += x * y;
+            }
+        }
+        result
+    })
+}
+
+fn ln_choose(n: usize, k: usize) -> f64 {
+    let k = k.min(n - k);
+    (1..=k).map(|i| ((n - k + i) as f64 / i as f64).ln()).sum()
+}
+
+/// The board to open `p` on in fair-guess mode, when the mines have to move
+/// for the click to be fair:
+/// - with no cell known to be safe the player has to guess, so `p` is made
+///   safe if anything on screen allows it;
+/// - with a safe cell around guessing wasn't needed, so `p` gets a mine if
+///   anything on screen allows it.
+///
+/// `None` when the mines can stay where they are. Mines move as little as
+/// they can and always the same way for the same position, so replays of
+/// fair-guess games play out the same.
+pub fn fair_layout(board: &Board, p: &Point) -> Option<Board> {
+    if !matches!(
+        board.at(p)?.state(),
+        MapElementCellState::Closed | MapElementCellState::Questioned
+    ) {
+        return None;
+    }
+    let probabilities = probabilities(board)?;
+    let probability = probabilities.iter().find(|(q, _)| q == p)?.1;
+    let any_safe = probabilities.iter().any(|(q, probability)| {
+        *probability < EPSILON
+            && !matches!(
+                board.at(q).unwrap().state(),
+                MapElementCellState::Flagged(_)
+            )
+    });
+    let is_mine = matches!(board.at(p)?, MapElement::Mine { .. });
+    match (any_safe, is_mine) {
+        (true, false) if probability > EPSILON => relayout(board, p, true),
+        (false, true) if probability < 1.0 - EPSILON => relayout(board, p, false),
+        _ => None,
+    }
+}
+
+/// A layout agreeing with every open number, with or without a mine at `p`,
+/// that keeps as many mines in place as it can.
+fn relayout(board: &Board, p: &Point, mine: bool) -> Option<Board> {
+    let Position {
+        unknown,
+        remaining,
+        mut constraints,
+    } = Position::of(board)?;
+    let target = unknown.iter().position(|q| q == p)?;
+    constraints.push((vec![target], mine as usize));
+
+    let mut constrained: Vec<usize> = constraints
+        .iter()
+        .flat_map(|(cells, _)| cells.iter().copied())
+        .collect();
+    constrained.sort_unstable();
+    constrained.dedup();
+    let interior: Vec<usize> = (0..unknown.len())
+        .filter(|c| constrained.binary_search(c).is_err())
+        .collect();
+    let was_mine: Vec<bool> = unknown
+        .iter()
+        .map(|q| matches!(board.at(q).unwrap(), MapElement::Mine { .. }))
+        .collect();
+    let mut touching: Vec<Vec<usize>> = vec![vec![]; unknown.len()];
+    for (k, (cells, _)) in constraints.iter().enumerate() {
+        for &c in cells {
+            touching[c].push(k);
+        }
+    }
+
+    let mut search = Relayout {
+        constraints: &constraints,
+        touching: &touching,
+        constrained: &constrained,
+        was_mine: &was_mine,
+        remaining,
+        interior: interior.len(),
+        mines_in: vec![0; constraints.len()],
+        open_in: constraints.iter().map(|(cells, _)| cells.len()).collect(),
+        is_mine: vec![false; unknown.len()],
+        steps: 0,
+    };
+    if !search.place(0, 0)? {
+        return None;
+    }
+    let mut is_mine = search.is_mine;
+
+    // cells no number touches take the mines that are left, where mines were
+    // before first
+    let mut left = remaining - constrained.iter().filter(|c| is_mine[**c]).count();
+    for keep in [true, false] {
+        for &c in &interior {
+            if left > 0 && was_mine[c] == keep {
+                is_mine[c] = true;
+                left -= 1;
+            }
+        }
+    }
+
+    let mines: Vec<Point> = board
+        .points()
+        .filter(|q| is_known_mine(board.at(q).unwrap()))
+        .chain(
+            unknown
+                .iter()
+                .zip(&is_mine)
+                .filter(|(_, is_mine)| **is_mine)
+                .map(|(q, _)| *q),
+        )
+        .collect();
+    Some(board.with_mines_at(&mines))
+}
+
+/// Depth-first search for the first arrangement of the constrained cells
+/// that fits, trying each cell as it was before the other way round.
+struct Relayout<'a> {
+    constraints: &'a [(Vec<usize>, usize)],
+    touching: &'a [Vec<usize>],
+    constrained: &'a [usize],
+    was_mine: &'a [bool],
+    remaining: usize,
+    interior: usize,
+    mines_in: Vec<usize>,
+    open_in: Vec<usize>,
+    is_mine: Vec<bool>,
+    steps: usize,
+}
+
+impl Relayout<'_> {
+    /// Whether the cells from `constrained[i]` on can be placed, given
+    /// `mines` before them. `None` when the search runs too long.
+    fn place(&mut self, i: usize, mines: usize) -> Option<bool> {
+        self.steps += 1;
+        if self.steps > MAX_SEARCH_STEPS {
+            return None;
+        }
+        if i == self.constrained.len() {
+            return Some(self.remaining - mines <= self.interior);
+        }
+        let c = self.constrained[i];
+        for is_mine in [self.was_mine[c], !self.was_mine[c]] {
+            if mines + is_mine as usize > self.remaining {
+                continue;
+            }
+            for &k in &self.touching[c] {
+                self.open_in[k] -= 1;
+                self.mines_in[k] += is_mine as usize;
+            }
+            let fits = self.touching[c].iter().all(|&k| {
+                let wanted = self.constraints[k].1;
+                self.mines_in[k] <= wanted && self.mines_in[k] + self.open_in[k] >= wanted
+            });
+            let placed = if fits {
+                self.place(i + 1, mines + is_mine as usize)
+            } else {
+                Some(false)
+            };
+            for &k in &self.touching[c] {
+                self.open_in[k] += 1;
+                self.mines_in[k] -= is_mine as usize;
+            }
+            if placed != Some(false) {
+                self.is_mine[c] = is_mine;
+                return placed;
+            }
+        }
+        Some(false)
+    }
+}
+
+/// The move the solver would make: a safe cell if there is one, then an
+/// unflagged certain mine, otherwise the least likely cell to be a mine.
+/// Ties go to the first cell in board order.
+pub fn next_move(board: &Board) -> Option<Move> {
+    if board.is_finished() {
+        return None;
+    }
+    let probabilities = probabilities(board)?;
+    let unflagged = |p: &Point| {
+        !matches!(
+            board.at(p).unwrap().state(),
+            MapElementCellState::Flagged(_)
+        )
+    };
+    let candidates: Vec<&(Point, f64)> =
+        probabilities.iter().filter(|(p, _)| unflagged(p)).collect();
+    if let Some((p, _)) = candidates
+        .iter()
+        .find(|(_, probability)| *probability == 0.0)
+    {
+        return Some(Move::Safe(*p));
+    }
+    if let Some((p, _)) = candidates
+        .iter()
+        .find(|(_, probability)| *probability == 1.0)
+    {
+        return Some(Move::Mine(*p));
+    }
+    candidates
+        .into_iter()
+        .fold(None, |best: Option<&(Point, f64)>, candidate| match best {
+            Some(best) if best.1 <= candidate.1 => Some(best),
+            _ => Some(candidate),
+        })
+        .map(|(point, probability)| Move::Guess {
+            point: *point,
+            probability: *probability,
+        })
+}
+
+/// A game the solver played.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Played {
+    pub board: Board,
+    pub moves: usize,
+    pub guesses: usize,
+}
+
+/// Lets the solver play `board` until the game is over or it's stuck.
+pub fn play(board: &Board) -> Played {
+    play_guessing(board, true)
+}
+
+/// Like `play`, but stops rather than guess after the first click, to see
+/// how far logic alone gets.
+pub fn play_safely(board: &Board) -> Played {
+    play_guessing(board, false)
+}
+
+fn play_guessing(board: &Board, keep_guessing: bool) -> Played {
+    let mut played = Played {
+        board: board.clone(),
+        moves: 0,
+        guesses: 0,
+    };
+    while let Some(next) = next_move(&played.board) {
+        if !keep_guessing && played.moves > 0 && matches!(next, Move::Guess { .. }) {
+            break;
+        }
+        match played.board.apply(&next.action()) {
+            Some(board) => played.board = board,
+            None => break,
+        }
+        played.moves += 1;
+        if let Move::Guess { .. } = next {
+            played.guesses += 1;
+        }
+    }
+    played
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{five_by_two_board, make_map};
+    use crate::{numbers_on_board, BoardState};
+    use pretty_assertions::assert_eq;
+
+    fn probability_of(probabilities: &[(Point, f64)], p: Point) -> f64 {
+        probabilities.iter().find(|(q, _)| *q == p).unwrap().1
+    }
+
+    #[test]
+    fn test_probabilities_with_certain_cells() {
+        let board = numbers_on_board(Board::new(make_map(
+            "X00
+             000
+             000",
+            "CCC
+             COO
+             COO",
+        )));
+        let probabilities = probabilities(&board).unwrap();
+        assert_eq!(
+            probabilities,
+            vec![
+                (Point::new(0, 0), 1.0),
+                (Point::new(1, 0), 0.0),
+                (Point::new(2, 0), 0.0),
+                (Point::new(0, 1), 0.0),
+                (Point::new(0, 2), 0.0),
+            ]
+        );
+        assert_eq!(next_move(&board), Some(Move::Safe(Point::new(1, 0))));
+    }
+
+    #[test]
+    fn test_probabilities_weigh_the_interior() {
+        let board = numbers_on_board(five_by_two_board())
+            .cascade_open_item(&Point::new(3, 1))
+            .unwrap();
+        let probabilities = probabilities(&board).unwrap();
+        assert_eq!(probabilities.len(), 4);
+        for (_, probability) in &probabilities {
+            assert!((probability - 0.5).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_probabilities_count_the_remaining_mines() {
+        // the bottom 1 puts a mine in the corner, and with that the numbers
+        // in the middle only leave room for the other one in the top middle
+        let board = numbers_on_board(Board::new(make_map(
+            "0X0
+             000
+             00X",
+            "CCC
+             OOO
+             OOC",
+        )));
+        let probabilities = probabilities(&board).unwrap();
+        assert_eq!(probability_of(&probabilities, Point::new(2, 2)), 1.0);
+        assert_eq!(probability_of(&probabilities, Point::new(0, 0)), 0.0);
+        assert_eq!(probability_of(&probabilities, Point::new(1, 0)), 1.0);
+    }
+
+    #[test]
+    fn test_play() {
+        let board = numbers_on_board(Board::new(make_map(
+            "00000
+             00000
+             0000X",
+            "CCCCC
+             CCCCC
+             CCCCC",
+        )));
+        let played = play(&board);
+        assert_eq!(played.board.state, BoardState::Won);
+        assert_eq!((played.moves, played.guesses), (1, 1));
+
+        // the first click leaves a 50/50 in the corner
+        let board = numbers_on_board(Board::new(make_map(
+            "00000
+             0000X",
+            "CCCCC
+             CCCCC",
+        )));
+        let played = play_safely(&board);
+        assert_eq!(played.board.state, BoardState::Playing);
+        assert_eq!((played.moves, played.guesses), (1, 1));
+        assert_eq!(play(&board).guesses, 2);
+    }
+
+    #[test]
+    fn test_fair_layout_saves_forced_guesses() {
+        let board = numbers_on_board(five_by_two_board()).with_fair_guesses(true);
+        // the first click is always a guess
+        let opened = board.apply(&Action::Open(Point::new(0, 0))).unwrap();
+        assert_eq!(opened.state, BoardState::Playing);
+
+        // the numbers leave two 50/50s and (0, 0) is a mine
+        let board = board.cascade_open_item(&Point::new(3, 1)).unwrap();
+        let fair = fair_layout(&board, &Point::new(0, 0)).unwrap();
+        assert!(matches!(
+            fair.at(&Point::new(0, 0)),
+            Some(MapElement::Number { count: 2, .. })
+        ));
+        assert_eq!(fair.mines, board.mines);
+        assert_eq!(fair_layout(&board, &Point::new(0, 0)), Some(fair));
+        // (1, 0) was safe already
+        assert_eq!(fair_layout(&board, &Point::new(1, 0)), None);
+    }
+
+    #[test]
+    fn test_fair_layout_punishes_needless_guesses() {
+        let board = numbers_on_board(Board::new(make_map(
+            "X000
+             0000",
+            "CCOO
+             CCOO",
+        )))
+        .with_fair_guesses(true);
+        // (1, 0) and (1, 1) are safe, so there was no need to guess
+        let opened = board.apply(&Action::Open(Point::new(0, 1))).unwrap();
+        assert_eq!(opened.state, BoardState::Failed);
+        let opened = board.apply(&Action::Open(Point::new(1, 1))).unwrap();
+        assert_eq!(opened.state, BoardState::Playing);
+    }
+
+    #[test]
+    fn test_probabilities_of_other_variants() {
+        let board = numbers_on_board(crate::tests::negative_board());
+        assert_eq!(probabilities(&board), None);
+    }
+}
@@ -0,0 +1,261 @@
+use std::fmt;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::daily::Day;
+use crate::difficulty::BoardSize;
+use crate::rating::{Rating, RatingError};
+use crate::{Board, BoardState, Variant};
+
+/// Everything we keep about the games played on one board size.
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub played: u32,
+    pub won: u32,
+    pub current_streak: u32,
+    pub best_streak: u32,
+    pub best_seconds: Option<f64>,
+}
+
+impl Record {
+    /// Adds a finished game, returning whether it's a new best time. Games
+    /// that had help are counted, but leave streaks and best times alone.
+    fn add(&mut self, won: bool, helped: bool, seconds: f64) -> bool {
+        self.played += 1;
+        self.won += won as u32;
+        if helped {
+            return false;
+        }
+        if !won {
+            self.current_streak = 0;
+            return false;
+        }
+        self.current_streak += 1;
+        self.best_streak = self.best_streak.max(self.current_streak);
+        let is_best = self.best_seconds.map_or(true, |best| seconds < best);
+        if is_best {
+            self.best_seconds = Some(seconds);
+        }
+        is_best
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "played {} · won {} · streak {} (best {})",
+            self.played, self.won, self.current_streak, self.best_streak
+        )?;
+        if let Some(best) = self.best_seconds {
+            write!(f, " · best time {:.2}s", best)?;
+        }
+        Ok(())
+    }
+}
+
+/// Statistics and personal bests for every board size played.
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Stats {
+    records: Vec<(BoardSize, Record)>,
+    /// The last day a daily challenge was played, for each size.
+    #[serde(default)]
+    daily: Vec<(BoardSize, Day)>,
+    #[serde(default)]
+    rating: Rating,
+}
+
+impl Stats {
+    pub fn get(&self, size: &BoardSize) -> Option<&Record> {
+        self.records
+            .iter()
+            .find(|(s, _)| s == size)
+            .map(|(_, record)| record)
+    }
+
+    /// Adds the game finished on `board`, returning whether it's a new best
+    /// time. Records are kept by size, so only games played by the plain
+    /// rules without the robot's help can set streaks and best times: not
+    /// other variants, nor games with lives or fair guesses.
+    pub fn record_game(&mut self, board: &Board, assisted: bool, seconds: f64) -> bool {
+        let size = BoardSize::of(board);
+        let won = board.state == BoardState::Won;
+        let helped = assisted
+            || board.variant != Variant::Classic
+            || board.lives.is_some()
+            || board.fair_guesses;
+        let index = match self.records.iter().position(|(s, _)| *s == size) {
+            Some(index) => index,
+            None => {
+                self.records.push((size, Record::default()));
+                self.records.len() - 1
+            }
+        };
+        self.records[index].1.add(won, helped, seconds)
+    }
+
+    pub fn rating(&self) -> &Rating {
+        &self.rating
+    }
+
+    /// Updates the rating with the game finished on `board` in `seconds`,
+    /// leaving it as it was if the board can't be rated.
+    pub fn rate_game(&mut self, board: &Board, seconds: f64) -> Result<(), RatingError> {
+        self.rating = self.rating.rate_game(board, seconds)?;
+        Ok(())
+    }
+
+    pub fn played_daily(&self, size: &BoardSize, day: &Day) -> bool {
+        self.daily.iter().any(|(s, d)| s == size && d == day)
+    }
+
+    pub fn record_daily(&mut self, size: BoardSize, day: Day) {
+        self.daily.retain(|(s, _)| *s != size);
+        self.daily.push((size, day));
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("stats are always serializable")
+    }
+
+    pub fn from_json(json: &str) -> Result<Stats, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Somewhere stats survive between sessions.
+pub trait StatsStorage {
+    type Error;
+
+    /// Reads the stats saved so far, or empty stats if there are none yet.
+    fn load(&self) -> Result<Stats, Self::Error>;
+    fn save(&mut self, stats: &Stats) -> Result<(), Self::Error>;
+}
+
+/// Keeps stats as JSON in a file.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileStorage {
+    pub path: std::path::PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl StatsStorage for FileStorage {
+    type Error = std::io::Error;
+
+    fn load(&self) -> Result<Stats, Self::Error> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(json) => Stats::from_json(&json).map_err(Into::into),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Stats::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn save(&mut self, stats: &Stats) -> Result<(), Self::Error> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&self.path, stats.to_json())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create_board, numbers_on_board, seeded_rand};
+    use pretty_assertions::assert_eq;
+
+    const BEGINNER: BoardSize = BoardSize {
+        width: 9,
+        height: 9,
+        mines: 10,
+    };
+
+    /// A finished game on a beginner board.
+    fn game(won: bool) -> Board {
+        let board = numbers_on_board(create_board(9, 9, 10, seeded_rand(0)));
+        Board {
+            state: if won {
+                BoardState::Won
+            } else {
+                BoardState::Failed
+            },
+            ..board
+        }
+    }
+
+    #[test]
+    fn test_record_game() {
+        let mut stats = Stats::default();
+        assert!(stats.record_game(&game(true), false, 20.0));
+        assert!(!stats.record_game(&game(true), false, 25.0));
+        assert!(stats.record_game(&game(true), false, 15.0));
+        assert!(!stats.record_game(&game(false), false, 1.0));
+        assert!(!stats.record_game(&game(true), false, 30.0));
+        // the robot's help doesn't make for a best time or a streak
+        assert!(!stats.record_game(&game(true), true, 5.0));
+        assert!(!stats.record_game(&game(false), true, 5.0));
+        assert_eq!(
+            stats.get(&BEGINNER),
+            Some(&Record {
+                played: 7,
+                won: 5,
+                current_streak: 1,
+                best_streak: 3,
+                best_seconds: Some(15.0),
+            })
+        );
+        assert_eq!(
+            stats.get(&BoardSize {
+                width: 16,
+                height: 16,
+                mines: 40
+            }),
+            None
+        );
+    }
+
+    #[test]
+    fn test_record_game_with_help() {
+        let mut stats = Stats::default();
+        assert!(stats.record_game(&game(true), false, 20.0));
+        assert!(!stats.record_game(&game(true).with_lives(3), false, 5.0));
+        assert!(!stats.record_game(&game(true).with_fair_guesses(true), false, 5.0));
+        let record = stats.get(&BEGINNER).unwrap();
+        assert_eq!(record.best_seconds, Some(20.0));
+        assert_eq!((record.played, record.won, record.best_streak), (3, 3, 1));
+    }
+
+    #[test]
+    fn test_record_daily() {
+        let mut stats = Stats::default();
+        let today = Day::from_days_since_epoch(20745);
+        let tomorrow = Day::from_days_since_epoch(20746);
+        assert!(!stats.played_daily(&BEGINNER, &today));
+        stats.record_daily(BEGINNER, today);
+        assert!(stats.played_daily(&BEGINNER, &today));
+        stats.record_daily(BEGINNER, tomorrow);
+        assert!(!stats.played_daily(&BEGINNER, &today));
+        assert!(stats.played_daily(&BEGINNER, &tomorrow));
+    }
+
+    #[test]
+    fn test_stats_from_json_without_daily() {
+        let stats = Stats::from_json(r#"{"records":[]}"#).unwrap();
+        assert_eq!(stats, Stats::default());
+    }
+
+    #[test]
+    fn test_file_storage() {
+        let path = std::env::temp_dir()
+            .join(format!("minesweeper-stats-{}", std::process::id()))
+            .join("stats.json");
+        let mut storage = FileStorage { path: path.clone() };
+        assert_eq!(storage.load().unwrap(), Stats::default());
+
+        let mut stats = Stats::default();
+        stats.record_game(&game(true), false, 12.5);
+        storage.save(&stats).unwrap();
+        assert_eq!(storage.load().unwrap(), stats);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
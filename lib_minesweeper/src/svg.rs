@@ -0,0 +1,206 @@
+//! Boards drawn as SVG images that look like the web game, to put positions
+//! in docs, issues and reports without a browser.
+
+use crate::BoardState::{self, Failed, Playing, Ready, Won};
+use crate::MapElement::{self, Mine, Number};
+use crate::MapElementCellState::{
+    Closed, Exploded, Flagged, Open, Questioned, Revealed, WronglyFlagged,
+};
+use crate::{Board, MineKind, Point, Variant};
+
+/// Pixels each cell takes, with its margin.
+const CELL_SIZE: usize = 40;
+const MARGIN: usize = 2;
+/// Around the board, like the padding of the web's board container.
+const PADDING: usize = 12;
+
+/// The colour of `count` on an open cell, from the `mines-N` classes of the
+/// web's style sheet.
+pub fn number_colour(count: i32) -> &'static str {
+    match count {
+        c if c < 0 => "#3a86ff",
+        1 => "#5296a5",
+        2 => "#50723c",
+        3 => "#d81159",
+        4 => "#ffbc42",
+        5 => "#218380",
+        6 => "#685369",
+        7 => "#8f2d56",
+        8 => "#423e28",
+        _ => "#666666",
+    }
+}
+
+/// Behind the board, which the web colours by how the game went.
+pub fn background(state: &BoardState) -> &'static str {
+    match state {
+        Won => "#057f74",
+        Failed => "#f4796b",
+        _ => "#ffffff",
+    }
+}
+
+/// What the web shows in a cell.
+fn symbol(
+    state: &BoardState,
+    element: &MapElement,
+    has_surrounding_mines: bool,
+    variant: &Variant,
+) -> String {
+    match (element, element.state()) {
+        (Mine { .. }, Exploded) => "💥".into(),
+        (Mine { .. }, _) if *state == Won => "🚩".into(),
+        (Mine { .. }, Open | Revealed) => "💣".into(),
+        (_, WronglyFlagged) => "❌".into(),
+        (_, Flagged(MineKind::Positive)) => "🚩".into(),
+        (_, Flagged(MineKind::Negative)) => "🏳️".into(),
+        (_, Flagged(MineKind::Secondary)) => "🏴".into(),
+        (_, Closed) => "❓".into(),
+        (_, Questioned) => "❔".into(),
+        (Number { .. }, _) if !has_surrounding_mines => String::new(),
+        (
+            Number {
+                count,
+                secondary_count,
+                ..
+            },
+            _,
+        ) if *variant == Variant::TwoColour => format!("{}·{}", count, secondary_count),
+        (Number { count, .. }, _) => count.to_string(),
+    }
+}
+
+/// The cell at `p`: its button, then whatever is written on it.
+fn cell(board: &Board, p: &Point) -> String {
+    let element = board.at(p).unwrap();
+    let x = PADDING + p.x as usize * CELL_SIZE + MARGIN;
+    let y = PADDING + p.y as usize * CELL_SIZE + MARGIN;
+    let size = CELL_SIZE - 2 * MARGIN;
+    let clickable = matches!(
+        (&board.state, element.state()),
+        (Ready | Playing, Closed | Questioned)
+    );
+    let fill = if clickable {
+        "url(#clickable)"
+    } else {
+        "#f9f9f9"
+    };
+    let mut svg = format!(
+        r#"<rect x="{}" y="{}" width="{}" height="{}" rx="8" fill="{}"/>"#,
+        x, y, size, size, fill
+    );
+    let text = symbol(
+        &board.state,
+        element,
+        board.has_surrounding_mines(p),
+        &board.variant,
+    );
+    if text.is_empty() {
+        return svg;
+    }
+    let (colour, italic) = match element {
+        Number {
+            state: Open, count, ..
+        } => (number_colour(*count), *count < 0),
+        Number {
+            state: Questioned, ..
+        }
+        | Mine {
+            state: Questioned, ..
+        } => ("#d81159", false),
+        _ => ("#666666", false),
+    };
+    // two counts need a smaller font to fit
+    let font_size = match element {
+        Number { state: Open, .. } if board.variant == Variant::TwoColour => 14,
+        _ => 20,
+    };
+    svg += &format!(
+        r#"<text x="{}" y="{}" font-size="{}" fill="{}"{}>{}</text>"#,
+        x + size / 2,
+        y + size / 2,
+        font_size,
+        colour,
+        if italic {
+            r#" font-style="italic""#
+        } else {
+            ""
+        },
+        text
+    );
+    svg
+}
+
+/// `board` as a standalone SVG image.
+pub fn to_svg(board: &Board) -> String {
+    let width = board.width * CELL_SIZE + 2 * PADDING;
+    let height = board.height * CELL_SIZE + 2 * PADDING;
+    let mut svg = format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}" font-family="Roboto, sans-serif" font-weight="bold" text-anchor="middle" dominant-baseline="central">
+<defs><linearGradient id="clickable" x1="0" y1="0" x2="0" y2="1"><stop offset="5%" stop-color="#f9f9f9"/><stop offset="100%" stop-color="#e9e9e9"/></linearGradient></defs>
+<rect width="{0}" height="{1}" fill="{2}"/>
+"##,
+        width,
+        height,
+        background(&board.state)
+    );
+    for p in board.points() {
+        svg += &cell(board, &p);
+        svg += "\n";
+    }
+    svg += "</svg>\n";
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::from_text;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_to_svg() {
+        let board = from_text(
+            "X..
+             ...
+
+             FOO
+             CQO",
+        )
+        .unwrap();
+        let svg = to_svg(&board);
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="144" height="104""#)
+        );
+        assert!(svg.ends_with("</svg>\n"));
+        // the background, then a button for each cell
+        assert_eq!(svg.matches("<rect").count(), 7);
+        assert_eq!(svg.matches(r#"fill="url(#clickable)""#).count(), 2);
+        assert!(svg.contains(r##"<rect width="144" height="104" fill="#ffffff"/>"##));
+        assert!(svg.contains(r##"fill="#5296a5">1</text>"##));
+        assert!(svg.contains(r##"fill="#d81159">❔</text>"##));
+        assert!(svg.contains(">🚩</text>"));
+    }
+
+    #[test]
+    fn test_to_svg_of_a_loss() {
+        let board = from_text(
+            "state failed
+
+             X..N
+             ....
+
+             EOOR
+             OOWO",
+        )
+        .unwrap();
+        let svg = to_svg(&board);
+        assert!(svg.contains(r##"<rect width="184" height="104" fill="#f4796b"/>"##));
+        assert!(svg.contains(">💥</text>"));
+        assert!(svg.contains(">💣</text>"));
+        assert!(svg.contains(">❌</text>"));
+        assert!(svg.contains(r##"fill="#3a86ff" font-style="italic">-1</text>"##));
+        // nothing can be clicked once the game is over
+        assert!(!svg.contains(r#"fill="url(#clickable)""#));
+    }
+}
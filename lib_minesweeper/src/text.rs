@@ -0,0 +1,351 @@
+//! A plain text format for boards, to save games and to write positions by
+//! hand. A file has a few settings, then where the mines are, then what
+//! state each cell is in, separated by blank lines:
+//!
+//! ```text
+//! state playing
+//! lives 2
+//! question-marks
+//!
+//! X....
+//! .X...
+//!
+//! CCOOO
+//! CFOOO
+//! ```
+//!
+//! Mines are `X`, `N` for negative and `Y` for secondary ones, anything else
+//! is `.`. Cells are `C`losed, `O`pen, `Q`uestioned, `E`xploded, `R`evealed
+//! or `W`rongly flagged, or flagged with `F`, `N` or `S` for the kind of mine
+//! the flag is for. Numbers are worked out from the mines. Lines starting
+//! with `#` are comments, and the settings can be left out.
+
+use std::fmt;
+
+use crate::{create_position, Board, BoardState, MapElement, MapElementCellState, MineKind};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum TextError {
+    /// A settings line that isn't one of ours.
+    BadSetting(String),
+    /// The mines and the cells aren't both there.
+    MissingGrid,
+    /// Rows of different lengths, or the two grids of different sizes.
+    BadShape,
+    BadCell(char),
+    /// A cell that can't be in that state in a game like this one, which
+    /// playing on from would break.
+    ImpossibleCell {
+        cell: char,
+        reason: &'static str,
+    },
+}
+
+impl fmt::Display for TextError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TextError::BadSetting(line) => write!(f, "unknown setting '{}'", line),
+            TextError::MissingGrid => write!(
+                f,
+                "expected the mines and then the cells, separated by a blank line"
+            ),
+            TextError::BadShape => write!(f, "every row of both grids must be as long"),
+            TextError::BadCell(c) => write!(f, "'{}' isn't a cell", c),
+            TextError::ImpossibleCell { cell, reason } => write!(f, "'{}' {}", cell, reason),
+        }
+    }
+}
+
+impl std::error::Error for TextError {}
+
+fn state_name(state: &BoardState) -> &'static str {
+    match state {
+        BoardState::NotReady | BoardState::Ready => "ready",
+        BoardState::Playing => "playing",
+        BoardState::Won => "won",
+        BoardState::Failed => "failed",
+    }
+}
+
+/// `board` in the text format.
+pub fn to_text(board: &Board) -> String {
+    let mut text = format!("state {}\n", state_name(&board.state));
+    if let Some(lives) = board.lives {
+        text += &format!("lives {}\n", lives);
+    }
+    if board.question_marks {
+        text += "question-marks\n";
+    }
+    if board.fair_guesses {
+        text += "fair-guesses\n";
+    }
+    let grid = |cell: &dyn Fn(&MapElement) -> char| -> String {
+        (0..board.height)
+            .map(|y| {
+                let mut row: String = (0..board.width)
+                    .map(|x| cell(board.at(&crate::Point::new(x, y)).unwrap()))
+                    .collect();
+                row.push('\n');
+                row
+            })
+            .collect()
+    };
+    text += "\n";
+    text += &grid(&|element| match element {
+        MapElement::Mine {
+            kind: MineKind::Positive,
+            ..
+        } => 'X',
+        MapElement::Mine {
+            kind: MineKind::Negative,
+            ..
+        } => 'N',
+        MapElement::Mine {
+            kind: MineKind::Secondary,
+            ..
+        } => 'Y',
+        MapElement::Number { .. } => '.',
+    });
+    text += "\n";
+    text += &grid(&|element| match element.state() {
+        MapElementCellState::Closed => 'C',
+        MapElementCellState::Open => 'O',
+        MapElementCellState::Flagged(MineKind::Positive) => 'F',
+        MapElementCellState::Flagged(MineKind::Negative) => 'N',
+        MapElementCellState::Flagged(MineKind::Secondary) => 'S',
+        MapElementCellState::Questioned => 'Q',
+        MapElementCellState::Exploded => 'E',
+        MapElementCellState::Revealed => 'R',
+        MapElementCellState::WronglyFlagged => 'W',
+    });
+    text
+}
+
+/// Reads a board written in the text format.
+pub fn from_text(text: &str) -> Result<Board, TextError> {
+    let mut blocks: Vec<Vec<&str>> = vec![vec![]];
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('#') {
+            continue;
+        }
+        match (line.is_empty(), blocks.last_mut()) {
+            (true, Some(block)) if block.is_empty() => (),
+            (true, _) => blocks.push(vec![]),
+            (false, Some(block)) => block.push(line),
+            (false, None) => unreachable!(),
+        }
+    }
+    blocks.retain(|block| !block.is_empty());
+    // settings are lowercase words, which grids never have, so a missing
+    // grid isn't taken for settings being left out
+    let is_setting = |line: &&str| line.chars().any(|c| c.is_ascii_lowercase() || c == ' ');
+    let settings = match blocks.first() {
+        Some(block) if block.iter().any(is_setting) => blocks.remove(0),
+        _ => vec![],
+    };
+    let (mines, cells) = match blocks.as_slice() {
+        [mines, cells] => (mines, cells),
+        _ => return Err(TextError::MissingGrid),
+    };
+
+    let width = mines[0].chars().count();
+    if mines.len() != cells.len()
+        || mines
+            .iter()
+            .chain(cells.iter())
+            .any(|row| row.chars().count() != width)
+    {
+        return Err(TextError::BadShape);
+    }
+    let map = mines
+        .iter()
+        .zip(cells.iter())
+        .map(|(mines, cells)| {
+            mines
+                .chars()
+                .zip(cells.chars())
+                .map(|(mine, cell)| {
+                    let state = match cell {
+                        'C' => MapElementCellState::Closed,
+                        'O' => MapElementCellState::Open,
+                        'F' => MapElementCellState::Flagged(MineKind::Positive),
+                        'N' => MapElementCellState::Flagged(MineKind::Negative),
+                        'S' => MapElementCellState::Flagged(MineKind::Secondary),
+                        'Q' => MapElementCellState::Questioned,
+                        'E' => MapElementCellState::Exploded,
+                        'R' => MapElementCellState::Revealed,
+                        'W' => MapElementCellState::WronglyFlagged,
+                        c => return Err(TextError::BadCell(c)),
+                    };
+                    let kind = match mine {
+                        'X' => MineKind::Positive,
+                        'N' => MineKind::Negative,
+                        'Y' => MineKind::Secondary,
+                        '.' => {
+                            return Ok(MapElement::Number {
+                                state,
+                                count: 0,
+                                secondary_count: 0,
+                            })
+                        }
+                        c => return Err(TextError::BadCell(c)),
+                    };
+                    Ok(MapElement::Mine { state, kind })
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut board = create_position(map);
+    for setting in &settings {
+        board = match setting.split_once(' ') {
+            Some(("state", state)) => Board {
+                state: match state.trim() {
+                    "ready" => BoardState::Ready,
+                    "playing" => BoardState::Playing,
+                    "won" => BoardState::Won,
+                    "failed" => BoardState::Failed,
+                    _ => return Err(TextError::BadSetting(setting.to_string())),
+                },
+                ..board
+            },
+            Some(("lives", lives)) => match lives.trim().parse() {
+                Ok(lives) => board.with_lives(lives),
+                Err(_) => return Err(TextError::BadSetting(setting.to_string())),
+            },
+            None if *setting == "question-marks" => board.with_question_marks(true),
+            None if *setting == "fair-guesses" => board.with_fair_guesses(true),
+            _ => return Err(TextError::BadSetting(setting.to_string())),
+        };
+    }
+    check_cells(&board)?;
+    Ok(board)
+}
+
+/// Makes sure every cell's state could have come from playing `board`.
+fn check_cells(board: &Board) -> Result<(), TextError> {
+    let failed = board.state == BoardState::Failed;
+    for p in board.points() {
+        let element = board.at(&p).unwrap();
+        let is_mine = matches!(element, MapElement::Mine { .. });
+        let (cell, reason) = match element.state() {
+            MapElementCellState::Exploded if !is_mine => ('E', "can only be on a mine"),
+            MapElementCellState::Revealed if !is_mine => ('R', "can only be on a mine"),
+            MapElementCellState::WronglyFlagged if is_mine => ('W', "can't be on a mine"),
+            MapElementCellState::Revealed if !failed => ('R', "only shows up in failed games"),
+            MapElementCellState::WronglyFlagged if !failed => {
+                ('W', "only shows up in failed games")
+            }
+            MapElementCellState::Exploded if !failed && board.lives.is_none() => {
+                ('E', "only shows up in failed games or ones with lives")
+            }
+            _ => continue,
+        };
+        return Err(TextError::ImpossibleCell { cell, reason });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::five_by_two_board;
+    use crate::{numbers_on_board, Action, Point};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_round_trip() {
+        let board = numbers_on_board(five_by_two_board())
+            .with_lives(2)
+            .with_question_marks(true)
+            .apply(&Action::Open(Point::new(3, 1)))
+            .unwrap()
+            .flag_item(&Point::new(1, 1));
+        let text = to_text(&board);
+        assert_eq!(
+            text,
+            "state playing
+lives 2
+question-marks
+
+X....
+.X...
+
+CCOOO
+CFOOO
+"
+        );
+        assert_eq!(from_text(&text), Ok(board));
+    }
+
+    #[test]
+    fn test_from_text_works_out_the_rest() {
+        let board = from_text(
+            "# a corner left to find
+             ..
+             .X
+
+             OO
+             OC",
+        )
+        .unwrap();
+        assert!(matches!(
+            board.at(&Point::new(0, 0)),
+            Some(MapElement::Number { count: 1, .. })
+        ));
+        assert_eq!(board.mines, 1);
+        assert_eq!(board.state, BoardState::Won);
+        assert!(board.lives.is_none());
+    }
+
+    #[test]
+    fn test_from_text_keeps_playing() {
+        let board = from_text(
+            "..X
+             ...
+
+             OCC
+             CCC",
+        )
+        .unwrap();
+        assert_eq!(board.state, BoardState::Playing);
+        // the last safe cells win the game
+        let board = [Point::new(1, 0), Point::new(0, 1)]
+            .iter()
+            .fold(board, |board, p| board.apply(&Action::Open(*p)).unwrap());
+        assert_eq!(board.state, BoardState::Playing);
+        let board = board.apply(&Action::Open(Point::new(2, 1))).unwrap();
+        assert_eq!(board.state, BoardState::Won);
+    }
+
+    #[test]
+    fn test_from_text_errors() {
+        assert_eq!(from_text("X.\n"), Err(TextError::MissingGrid));
+        assert_eq!(from_text("lives 2\n\nX.\n"), Err(TextError::MissingGrid));
+        assert_eq!(from_text("X.\n\nCCC\n"), Err(TextError::BadShape));
+        assert_eq!(from_text("X?\n\nCC\n"), Err(TextError::BadCell('?')));
+        assert_eq!(
+            from_text("lives many\n\nX.\n\nCC\n"),
+            Err(TextError::BadSetting("lives many".into()))
+        );
+    }
+
+    #[test]
+    fn test_from_text_rejects_impossible_cells() {
+        let cell_error = |text: &str| match from_text(text) {
+            Err(TextError::ImpossibleCell { cell, .. }) => Some(cell),
+            _ => None,
+        };
+        // numbers can't blow up or be revealed like mines
+        assert_eq!(cell_error("state failed\n\nX.\n\nEE\n"), Some('E'));
+        assert_eq!(cell_error("state failed\n\nX.\n\nER\n"), Some('R'));
+        assert_eq!(cell_error("state failed\n\nX.\n\nWC\n"), Some('W'));
+        // what a lost game shows, in one still being played
+        assert_eq!(cell_error("state playing\n\nX.\n..\n\nWC\nCC\n"), Some('W'));
+        assert_eq!(cell_error("state playing\n\nX.\n\nRC\n"), Some('R'));
+        assert_eq!(cell_error("state won\n\nX.\n\nCW\n"), Some('W'));
+        assert_eq!(cell_error("state playing\n\nX.\n\nEC\n"), Some('E'));
+        // hitting a mine with a life to spare
+        assert!(from_text("state playing\nlives 1\n\nX.\n\nEC\n").is_ok());
+        assert!(from_text("state failed\n\nX.N\n...\n\nEWR\nOOC\n").is_ok());
+    }
+}
@@ -1,7 +1,21 @@
 #![recursion_limit = "512"]
 
 use lib_minesweeper::create_board;
+use lib_minesweeper::daily::Day;
+use lib_minesweeper::difficulty::BoardSize;
+use lib_minesweeper::difficulty::Difficulty;
+use lib_minesweeper::leaderboard::top_path;
+use lib_minesweeper::leaderboard::Entry;
+use lib_minesweeper::leaderboard::Submission;
 use lib_minesweeper::numbers_on_board;
+use lib_minesweeper::replay::Claim;
+use lib_minesweeper::replay::Replay;
+use lib_minesweeper::score::score;
+use lib_minesweeper::score::Clicks;
+use lib_minesweeper::seeded_rand;
+use lib_minesweeper::stats::Stats;
+use lib_minesweeper::stats::StatsStorage;
+use lib_minesweeper::Action;
 use lib_minesweeper::Board;
 use lib_minesweeper::BoardState;
 use lib_minesweeper::BoardState::Failed;
@@ -13,121 +27,186 @@ use lib_minesweeper::MapElement;
 use lib_minesweeper::MapElement::Mine;
 use lib_minesweeper::MapElement::Number;
 use lib_minesweeper::MapElementCellState::Closed;
+use lib_minesweeper::MapElementCellState::Exploded;
 use lib_minesweeper::MapElementCellState::Flagged;
 use lib_minesweeper::MapElementCellState::Open;
+use lib_minesweeper::MapElementCellState::Questioned;
+use lib_minesweeper::MapElementCellState::Revealed;
+use lib_minesweeper::MapElementCellState::WronglyFlagged;
+use lib_minesweeper::MineKind;
 use lib_minesweeper::Point;
+use lib_minesweeper::Variant;
 
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
 
 use serde_derive::{Deserialize, Serialize};
-//use yew::format::Json;
 use yew::prelude::*;
 
 use js_sys::Date;
 
-//use yew::services::storage::{Area, StorageService};
-
-fn small_board() -> Board {
+/// A new board and the seed it was made from, which the leaderboard needs
+/// to rank games on it.
+fn random_board(difficulty: &Difficulty) -> (Board, u64) {
     use rand::Rng;
-    let width = 10;
-    let height = 10;
-    let mines = 10;
+    let size = difficulty.size();
+    let seed = rand::thread_rng().gen();
 
-    let board = create_board(width, height, mines, |x, y| {
-        rand::thread_rng().gen_range(x..y)
-    });
+    let board = create_board(size.width, size.height, size.mines, seeded_rand(seed));
 
-    numbers_on_board(board)
+    (numbers_on_board(board), seed)
 }
 
-fn medium_board() -> Board {
-    use rand::Rng;
-    let width = 16;
-    let height = 16;
-    let mines = 40;
-
-    let board = create_board(width, height, mines, |x, y| {
-        rand::thread_rng().gen_range(x..y)
-    });
-
-    numbers_on_board(board)
+/// Today's daily challenge at the given difficulty, and the seed it was
+/// made from.
+fn daily_board(difficulty: &Difficulty) -> (Board, u64, Day) {
+    let today = today();
+    let size = difficulty.size();
+    let board = lib_minesweeper::daily::daily_board(&today, &size);
+    (board, today.seed(&size), today)
 }
 
-fn large_board() -> Board {
-    use rand::Rng;
-    let width = 16;
-    let height = 30;
-    let mines = 99;
-
-    let board = create_board(width, height, mines, |x, y| {
-        rand::thread_rng().gen_range(x..y)
-    });
-
-    numbers_on_board(board)
+fn today() -> Day {
+    Day::from_days_since_epoch((Date::now() / 86_400_000_f64).floor() as i64)
 }
 
+/// Mine hits survived per game when lives are turned on, on top of the one
+/// every game has: the game ends on hit number `EXTRA_LIVES + 1`.
+const EXTRA_LIVES: u32 = 3;
+
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 enum Mode {
     Flagging,
     Digging,
 }
 
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
-enum Difficulty {
-    Easy,
-    Medium,
-    Hard,
-}
 struct Model {
-    //storage: StorageService,
+    storage: LocalStorage,
+    stats: Stats,
+    /// Whether the last finished game was a personal best.
+    personal_best: bool,
+    /// Top times for the board last finished, when there's a leaderboard.
+    leaderboard: Vec<Entry>,
     state: State,
 }
 
 enum Msg {
     ToggleDifficulty,
     ToggleMode,
+    ToggleLives,
+    ToggleFairGuesses,
+    ToggleQuestionMarks,
     UpdateBoard { point: Point },
     RunRobot,
+    PlayDaily,
+    GameTimed { seconds: f64 },
+    LeaderboardLoaded { entries: Vec<Entry> },
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct State {
     difficulty: Difficulty,
     mode: Mode,
+    lives: bool,
+    fair_guesses: bool,
+    /// Whether right clicks cycle through a question mark after the flag.
+    question_marks: bool,
     board: Board,
+    clicks: Clicks,
+    /// How long the game took, once it's over.
+    seconds: Option<f64>,
+    /// The day whose daily challenge is being played, if any.
+    daily: Option<Day>,
+    replay: Replay,
+    /// When the board was shown, as milliseconds since the epoch.
+    shown_at: f64,
+    /// Games the robot helped with don't go to the leaderboard.
+    robot_used: bool,
+}
+
+const STATS_KEY: &str = "jgpaiva.minesweeper.stats";
+const PLAYER_KEY: &str = "jgpaiva.minesweeper.player";
+
+/// Where the leaderboard server lives, set when building to turn it on.
+const LEADERBOARD_URL: Option<&str> = option_env!("MINESWEEPER_LEADERBOARD_URL");
+
+/// Keeps stats as JSON in the browser's local storage.
+struct LocalStorage;
+
+impl LocalStorage {
+    fn storage() -> Result<web_sys::Storage, JsValue> {
+        web_sys::window()
+            .ok_or("no window")?
+            .local_storage()?
+            .ok_or_else(|| "storage was disabled by the user".into())
+    }
 }
 
-//const KEY: &'static str = "jgpaiva.minesweeper.self";
+impl StatsStorage for LocalStorage {
+    type Error = JsValue;
+
+    fn load(&self) -> Result<Stats, JsValue> {
+        match Self::storage()?.get_item(STATS_KEY)? {
+            Some(json) => Stats::from_json(&json).map_err(|e| e.to_string().into()),
+            None => Ok(Stats::default()),
+        }
+    }
+
+    fn save(&mut self, stats: &Stats) -> Result<(), JsValue> {
+        Self::storage()?.set_item(STATS_KEY, &stats.to_json())
+    }
+}
 
 impl Component for Model {
     type Message = Msg;
     type Properties = ();
     fn create(_: &Context<Self>) -> Self {
-        //let storage = StorageService::new(Area::Local).expect("storage was disabled by the user");
-        //        let difficulty = {
-        //            if let Json(Ok(restored_model)) = storage.restore(KEY) {
-        //                restored_model
-        //            } else {
-        //
-        //            }
-        //        };
+        let storage = LocalStorage;
+        let stats = storage.load().unwrap_or_else(|e| {
+            log::warn!("couldn't load stats: {:?}", e);
+            Stats::default()
+        });
+        let (board, seed) = random_board(&Difficulty::Beginner);
+        let board = board.with_question_marks(true);
         let state = State {
-            difficulty: Difficulty::Easy,
+            difficulty: Difficulty::Beginner,
             mode: Mode::Digging,
-            board: small_board(),
+            lives: false,
+            fair_guesses: false,
+            question_marks: true,
+            clicks: Clicks::default(),
+            seconds: None,
+            daily: None,
+            replay: Replay::seeded(&board, seed),
+            shown_at: Date::now(),
+            robot_used: false,
+            board,
         };
         Self {
-            //storage,
+            storage,
+            stats,
+            personal_best: false,
+            leaderboard: vec![],
             state,
         }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
             Msg::ToggleDifficulty => self.toggle_difficulty(),
             Msg::ToggleMode => self.toggle_mode(),
+            Msg::ToggleLives => self.toggle_lives(),
+            Msg::ToggleFairGuesses => self.toggle_fair_guesses(),
+            Msg::ToggleQuestionMarks => self.toggle_question_marks(),
             Msg::UpdateBoard { point } => self.update_board(point),
             Msg::RunRobot => self.run_robot(),
+            Msg::PlayDaily => self.play_daily(),
+            Msg::GameTimed { seconds } => {
+                self.record_game(seconds);
+                self.load_leaderboard(ctx);
+            }
+            Msg::LeaderboardLoaded { entries } => self.leaderboard = entries,
         }
         true
     }
@@ -154,6 +233,30 @@ impl Component for Model {
                      onclick={ctx.link().callback(|_| Msg::RunRobot)} >
                         { self.render_robot()}
                     </div>
+                    <div
+                     id="lives-button"
+                     class="clickable item"
+                     onclick={ctx.link().callback(|_| Msg::ToggleLives)} >
+                        { self.render_lives() }
+                    </div>
+                    <div
+                     id="fair-guesses-button"
+                     class="clickable item"
+                     onclick={ctx.link().callback(|_| Msg::ToggleFairGuesses)} >
+                        { self.render_fair_guesses() }
+                    </div>
+                    <div
+                     id="question-marks-button"
+                     class="clickable item"
+                     onclick={ctx.link().callback(|_| Msg::ToggleQuestionMarks)} >
+                        { self.render_question_marks() }
+                    </div>
+                    <div
+                     id="daily-button"
+                     class="clickable item"
+                     onclick={ctx.link().callback(|_| Msg::PlayDaily)} >
+                        { self.render_daily() }
+                    </div>
                     <TimeKeeper op={
                         match self.state.board.state {
                             Won => TimeKeeperOp::Stopped,
@@ -161,8 +264,12 @@ impl Component for Model {
                             Playing => TimeKeeperOp::Counting,
                             Ready => TimeKeeperOp::Reset,
                             NotReady => unreachable!(),
-                        }}/>
+                        }}
+                     on_stop={ctx.link().callback(|seconds| Msg::GameTimed { seconds })}/>
                 </div>
+                { self.render_score() }
+                { self.render_stats() }
+                { self.render_leaderboard() }
                 <div id="board_game_placeholder">
                     <div id="board_game" class="flex-container">
                         {
@@ -180,6 +287,8 @@ impl Component for Model {
                                                                 board_state={board.state.clone()}
                                                                 board_width={board.width}
                                                                 element={board.at(&Point::new(x,y)).unwrap().clone()}
+                                                                has_surrounding_mines={board.has_surrounding_mines(&Point::new(x,y))}
+                                                                variant={board.variant.clone()}
                                                                 update_signal={ctx.link().callback(|msg:Msg| msg)}/>
                                                         }
                                                     }
@@ -195,21 +304,59 @@ impl Component for Model {
 
 impl Model {
     fn toggle_difficulty(&mut self) {
-        let (new_board, new_difficulty) = match (
-            self.state.board.state.clone(),
-            self.state.difficulty.clone(),
-        ) {
-            (Ready, Difficulty::Easy) => (medium_board(), Difficulty::Medium),
-            (Ready, Difficulty::Medium) => (large_board(), Difficulty::Hard),
-            (Ready, Difficulty::Hard) => (small_board(), Difficulty::Easy),
-            (_, Difficulty::Easy) => (small_board(), Difficulty::Easy),
-            (_, Difficulty::Medium) => (medium_board(), Difficulty::Medium),
-            (_, Difficulty::Hard) => (large_board(), Difficulty::Hard),
+        let new_difficulty = match self.state.board.state {
+            Ready => self.state.difficulty.next(),
+            _ => self.state.difficulty,
         };
+        self.state.difficulty = new_difficulty;
+        let (board, seed) = random_board(&new_difficulty);
+        self.start_game(self.with_options(board), seed, None);
+    }
+    fn toggle_lives(&mut self) {
+        self.state.lives = !self.state.lives;
+        let (board, seed) = random_board(&self.state.difficulty);
+        self.start_game(self.with_options(board), seed, None);
+    }
+    fn toggle_fair_guesses(&mut self) {
+        self.state.fair_guesses = !self.state.fair_guesses;
+        let (board, seed) = random_board(&self.state.difficulty);
+        self.start_game(self.with_options(board), seed, None);
+    }
+    /// Starts a new game, as replays of this one expect right clicks to
+    /// keep doing what they did.
+    fn toggle_question_marks(&mut self) {
+        self.state.question_marks = !self.state.question_marks;
+        let (board, seed) = random_board(&self.state.difficulty);
+        self.start_game(self.with_options(board), seed, None);
+    }
+    /// Starts today's daily challenge, which is always played without lives
+    /// or fair guesses so that everyone plays the same game.
+    fn play_daily(&mut self) {
+        let (board, seed, today) = daily_board(&self.state.difficulty);
+        self.state.lives = false;
+        self.state.fair_guesses = false;
+        self.start_game(board, seed, Some(today));
+    }
+    fn start_game(&mut self, board: Board, seed: u64, daily: Option<Day>) {
+        let board = board.with_question_marks(self.state.question_marks);
         self.state = State {
-            difficulty: new_difficulty,
-            board: new_board,
+            clicks: Clicks::default(),
+            seconds: None,
+            daily,
+            replay: Replay::seeded(&board, seed),
+            shown_at: Date::now(),
+            robot_used: false,
+            board,
             ..self.state.clone()
+        };
+        self.leaderboard = vec![];
+    }
+    fn with_options(&self, board: Board) -> Board {
+        let board = board.with_fair_guesses(self.state.fair_guesses);
+        if self.state.lives {
+            board.with_lives(EXTRA_LIVES)
+        } else {
+            board
         }
     }
     fn toggle_mode(&mut self) {
@@ -241,9 +388,10 @@ impl Model {
     fn render_difficulty(&self) -> Html {
         html! {
             match self.state.difficulty {
-                Difficulty::Easy => "😀",
-                Difficulty::Medium => "🤨",
-                Difficulty::Hard => "🧐",
+                Difficulty::Beginner => "😀",
+                Difficulty::Intermediate => "🤨",
+                Difficulty::Expert => "🧐",
+                Difficulty::Custom(_) => "🛠️",
             }
         }
     }
@@ -274,6 +422,83 @@ impl Model {
         }
     }
 
+    /// The mine hits that can still be survived, shown as extra lives: with
+    /// `+0` left, the next mine ends the game.
+    fn render_lives(&self) -> String {
+        match self.state.board.lives {
+            Some(lives) => format!("❤️+{}", lives),
+            None => String::from("🖤"),
+        }
+    }
+
+    fn render_fair_guesses(&self) -> &str {
+        if self.state.board.fair_guesses {
+            "⚖️"
+        } else {
+            "🎲"
+        }
+    }
+
+    fn render_question_marks(&self) -> &str {
+        if self.state.question_marks {
+            "❔"
+        } else {
+            "🚩"
+        }
+    }
+
+    fn render_daily(&self) -> &str {
+        let size = self.state.difficulty.size();
+        if self.stats.played_daily(&size, &today()) {
+            "✅"
+        } else {
+            "📅"
+        }
+    }
+
+    fn render_score(&self) -> Html {
+        match (&self.state.board.state, self.state.seconds) {
+            (Won, Some(seconds)) => html! {
+                <div id="score" class="flex-container score">
+                    { score(&self.state.board, &self.state.clicks, seconds).to_string() }
+                </div>
+            },
+            _ => html! {},
+        }
+    }
+
+    fn render_stats(&self) -> Html {
+        if self.state.seconds.is_none() {
+            return html! {};
+        }
+        match self.stats.get(&BoardSize::of(&self.state.board)) {
+            Some(record) => html! {
+                <div id="stats" class="flex-container score">
+                    { if self.personal_best { "🏆 New personal best! " } else { "" } }
+                    { format!("{} · {}", record, self.stats.rating()) }
+                </div>
+            },
+            None => html! {},
+        }
+    }
+
+    fn render_leaderboard(&self) -> Html {
+        if self.leaderboard.is_empty() {
+            return html! {};
+        }
+        html! {
+            <div id="leaderboard" class="flex-container score">
+                <ol>
+                    {
+                        self.leaderboard.iter().map(|entry| html! {
+                            <li>{ format!("{} · {:.2}s · 3BV/s {:.2}", entry.player, entry.seconds, entry.three_bv_per_second) }</li>
+                        }).collect::<Html>()
+                    }
+                </ol>
+            </div>
+        }
+    }
+
     fn render_break(&self) -> Html {
         html! {
             <div class="break">
@@ -281,17 +506,107 @@ impl Model {
         }
     }
 
-    fn update_board(&mut self, p: Point) {
-        match self.state.mode {
-            Mode::Digging => {
-                let new_board = self.state.board.cascade_open_item(&p);
-                if let Some(b) = new_board {
-                    self.state.board = b
+    fn record_game(&mut self, seconds: f64) {
+        self.state.seconds = Some(seconds);
+        let board = &self.state.board;
+        self.personal_best = false;
+        if let Some(day) = self.state.daily {
+            let size = BoardSize::of(board);
+            // only the first daily challenge of each day counts
+            if self.stats.played_daily(&size, &day) {
+                return;
+            }
+            self.stats.record_daily(size, day);
+        }
+        self.personal_best = self
+            .stats
+            .record_game(board, self.state.robot_used, seconds);
+        if !self.state.robot_used {
+            if let Err(e) = self.stats.rate_game(board, seconds) {
+                log::info!("not rated: {}", e);
+            }
+        }
+        if let Err(e) = self.storage.save(&self.stats) {
+            log::warn!("couldn't save stats: {:?}", e);
+        }
+    }
+
+    /// Sends the game just finished to the leaderboard, if there is one and
+    /// the robot didn't help, then fetches the top times for its board.
+    fn load_leaderboard(&mut self, ctx: &Context<Self>) {
+        let url = match LEADERBOARD_URL {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None => return,
+        };
+        // the leaderboard only ranks games without lives or fair guesses
+        let ranked = !self.state.robot_used && !self.state.lives && !self.state.fair_guesses;
+        let submission = match (&self.state.board.state, ranked) {
+            (Won | Failed, true) => Self::player().map(|player| Submission {
+                player,
+                claim: Claim {
+                    state: self.state.board.state.clone(),
+                    seconds: self.state.replay.seconds(),
+                    clicks: self.state.clicks.clone(),
+                },
+                replay: self.state.replay.clone(),
+            }),
+            _ => None,
+        };
+        let top_url = format!("{}{}", url, top_path(&BoardSize::of(&self.state.board)));
+        ctx.link().send_future(async move {
+            if let Some(submission) = submission {
+                let body = serde_json::to_string(&submission).expect("submissions serialize");
+                if let Err(e) = fetch("POST", &format!("{}/games", url), Some(body)).await {
+                    log::warn!("couldn't submit to the leaderboard: {:?}", e);
                 }
             }
-            Mode::Flagging => {
-                self.state.board = self.state.board.flag_item(&p);
+            let entries = fetch("GET", &top_url, None)
+                .await
+                .and_then(|text| serde_json::from_str(&text).map_err(|e| e.to_string().into()))
+                .unwrap_or_else(|e| {
+                    log::warn!("couldn't load the leaderboard: {:?}", e);
+                    vec![]
+                });
+            Msg::LeaderboardLoaded { entries }
+        });
+    }
+
+    /// The name to show on the leaderboard, asked for the first time around.
+    fn player() -> Option<String> {
+        let storage = LocalStorage::storage().ok()?;
+        if let Ok(Some(player)) = storage.get_item(PLAYER_KEY) {
+            return Some(player);
+        }
+        let player = web_sys::window()?
+            .prompt_with_message("Your name for the leaderboard")
+            .ok()??;
+        let player = player.trim().to_string();
+        if player.is_empty() {
+            return None;
+        }
+        let _ = storage.set_item(PLAYER_KEY, &player);
+        Some(player)
+    }
+
+    fn update_board(&mut self, p: Point) {
+        if self.state.board.is_finished() {
+            return;
+        }
+        let action = match self.state.mode {
+            Mode::Digging
+                if matches!(self.state.board.at(&p), Some(Number { state: Open, .. })) =>
+            {
+                Action::Chord(p)
             }
+            Mode::Digging => Action::Open(p),
+            Mode::Flagging => Action::Flag(p),
+        };
+        let new_board = self.state.board.apply(&action);
+        self.state.clicks.record(&action, new_board.is_some());
+        let millis = (Date::now() - self.state.shown_at) as i64;
+        self.state.replay.record(millis, action);
+        if let Some(b) = new_board {
+            self.state.board = b
         }
     }
 
@@ -303,72 +618,45 @@ impl Model {
         for x in 0..board.width {
             for y in 0..board.height {
                 let p = Point::new(x, y);
-                if let Some(board) = Self::run_robot_on_point(board, p) {
+                if let Some(board) = board.run_robot_on_point(p) {
                     self.state.board = board;
+                    self.state.robot_used = true;
                     return;
                 }
             }
         }
     }
+}
 
-    fn run_robot_on_point(board: &Board, p: Point) -> Option<Board> {
-        let el = board.at(&p).unwrap();
-        let Number {
-            state: Open,
-            count: mine_count,
-        } = el else {
-            return None
-        };
-        if *mine_count == 0 {
-            return None;
-        }
-        let surrounding_points = board.surrounding_points(&p);
-        let surrounding_els: Vec<_> = surrounding_points
-            .iter()
-            .map(|p| (p, board.at(p).unwrap().clone()))
-            .filter(|(_p, el)| {
-                !matches!(
-                    el,
-                    Number {
-                        state: Open,
-                        count: 0
-                    }
-                )
-            })
-            .collect();
-        let mut unopened = surrounding_els
-            .iter()
-            .filter(|(_p, el)| !matches!(el, Number { state: Open, .. }));
-        let flagged_count = surrounding_els
-            .iter()
-            .filter(|(_p, el)| {
-                matches!(el, Mine { state: Flagged } | Number { state: Flagged, .. })
-            })
-            .count();
-        let unopened_count = unopened.clone().count();
-        if *mine_count == unopened_count as i32 && flagged_count < unopened_count {
-            let (p, _el) = unopened
-                .find(|(_p, el)| {
-                    !matches!(el, Mine { state: Flagged } | Number { state: Flagged, .. })
-                })
-                .unwrap();
-            return Some(board.flag_item(p));
-        }
-        if *mine_count == flagged_count as i32 && unopened_count - flagged_count > 0 {
-            let (p, _el) = unopened
-                .find(|(_p, el)| {
-                    !matches!(el, Mine { state: Flagged } | Number { state: Flagged, .. })
-                })
-                .unwrap();
-            return board.cascade_open_item(p);
-        }
-        None
+/// Makes a request and gets the response's text, failing unless it's a 2xx.
+async fn fetch(method: &str, url: &str, body: Option<String>) -> Result<String, JsValue> {
+    let mut init = web_sys::RequestInit::new();
+    init.method(method);
+    if let Some(body) = body {
+        init.body(Some(&JsValue::from_str(&body)));
+    }
+    let request = web_sys::Request::new_with_str_and_init(url, &init)?;
+    request.headers().set("Content-Type", "application/json")?;
+    let window = web_sys::window().ok_or("no window")?;
+    let response: web_sys::Response = JsFuture::from(window.fetch_with_request(&request))
+        .await?
+        .dyn_into()?;
+    let text = JsFuture::from(response.text()?)
+        .await?
+        .as_string()
+        .unwrap_or_default();
+    if response.ok() {
+        Ok(text)
+    } else {
+        Err(text.into())
     }
 }
 
-#[derive(Copy, Clone, Properties, PartialEq)]
+#[derive(Clone, Properties, PartialEq)]
 struct TimeKeeperProps {
     op: TimeKeeperOp,
+    /// Gets the game's duration in seconds whenever the timer stops.
+    on_stop: Callback<f64>,
 }
 
 #[derive(Copy, Clone, PartialEq)]
@@ -427,11 +715,13 @@ impl Component for TimeKeeper {
             (TimeKeeperOp::Counting, TimeKeeperOp::Counting) => true,
             (TimeKeeperOp::Counting, TimeKeeperOp::Stopped) => {
                 self.state.stopped_at = Some(Date::new_0());
+                props.on_stop.emit(self.elapsed_seconds());
                 true
             }
             (TimeKeeperOp::Reset, TimeKeeperOp::Stopped) => {
                 self.state.started_at = Some(Date::new_0());
                 self.state.stopped_at = Some(Date::new_0());
+                props.on_stop.emit(self.elapsed_seconds());
                 true
             }
             (TimeKeeperOp::Stopped, TimeKeeperOp::Stopped) => false,
@@ -455,6 +745,18 @@ impl Component for TimeKeeper {
 }
 
 impl TimeKeeper {
+    fn elapsed_seconds(&self) -> f64 {
+        match (&self.state.started_at, &self.state.stopped_at) {
+            (Some(started_at), Some(stopped_at)) => {
+                (stopped_at.get_time() - started_at.get_time()) / 1000_f64
+            }
+            (Some(started_at), None) => {
+                (Date::new_0().get_time() - started_at.get_time()) / 1000_f64
+            }
+            (None, _) => 0_f64,
+        }
+    }
+
     fn render_timer(&self) -> String {
         match (&self.state.started_at, &self.state.stopped_at) {
             (Some(started_at), None) => {
@@ -485,6 +787,8 @@ struct BoardItemProps {
     board_state: BoardState,
     board_width: usize,
     element: MapElement,
+    has_surrounding_mines: bool,
+    variant: Variant,
     update_signal: Callback<Msg>,
 }
 
@@ -503,7 +807,9 @@ impl Component for BoardItem {
             && old_props.y == props.y
             && old_props.board_state == props.board_state
             && old_props.board_width == props.board_width
-            && old_props.element == props.element)
+            && old_props.element == props.element
+            && old_props.has_surrounding_mines == props.has_surrounding_mines
+            && old_props.variant == props.variant)
     }
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
@@ -524,15 +830,16 @@ impl Component for BoardItem {
             <div
              class={
                  match(&props.board_state, &props.element) {
-                     (Ready, Number { state: Closed, .. })
-                         | (Ready, Mine { state: Closed, .. })
-                         | (Playing, Number { state: Closed, .. })
-                         | (Playing, Mine { state: Closed, .. }) => {
-                             String::from("item clickable2")
-                         },
-                     (Playing, Number {state: Open, count})
-                         | (Won,Number {count, ..})
-                         | (Failed,Number {count, ..}) => {
+                     (Ready | Playing, Number { state: Closed, .. } | Mine { state: Closed, .. }) => {
+                         String::from("item clickable2")
+                     },
+                     (Ready | Playing, Number { state: Questioned, .. } | Mine { state: Questioned, .. }) => {
+                         String::from("item clickable2 questioned")
+                     },
+                     (_, Number { state: Open, count, .. }) if *count < 0 => {
+                         String::from("item not-clickable2 mines-negative")
+                     },
+                     (_, Number { state: Open, count, .. }) => {
                          format!("item not-clickable2 mines-{}", count)
                      },
                      _ => String::from("item not-clickable2")
@@ -541,23 +848,28 @@ impl Component for BoardItem {
                 onclick={ctx.link().callback(move |_| {Msg::UpdateBoard {point:Point::new(x,y)}})} >
                 <div style="width:100%; text-align:center"> {
                     match (&props.board_state, &props.element) {
-                        (Ready, Number { state: Flagged, .. })
-                            | (Ready, Mine { state: Flagged, .. })
-                            | (Playing, Number { state: Flagged, .. })
-                            | (Playing, Mine { state: Flagged, .. }) => {
-                                String::from("🚩")
-                            }
-                        (Ready, Number { state: Closed, .. })
-                            | (Ready, Mine { state: Closed, .. })
-                            | (Playing, Number { state: Closed, .. })
-                            | (Playing, Mine { state: Closed, .. }) => {
-                                String::from("❓")
-                            }
-                        (_, Number { count:0, .. }) => String::from(""),
-                        (_, Number { count, .. }) => format!("{}",count),
-                        (Failed, Mine { .. }) => String::from("💣"),
+                        (_, Mine { state: Exploded, .. }) => String::from("💥"),
                         (Won, Mine { .. }) => String::from("🚩"),
-                        _ => unreachable!(),
+                        (_, Mine { state: Open | Revealed, .. }) => String::from("💣"),
+                        (_, Number { state: WronglyFlagged, .. } | Mine { state: WronglyFlagged, .. }) => {
+                            String::from("❌")
+                        }
+                        (_, Number { state: Flagged(MineKind::Negative), .. } | Mine { state: Flagged(MineKind::Negative), .. }) => {
+                            String::from("🏳️")
+                        }
+                        (_, Number { state: Flagged(MineKind::Secondary), .. } | Mine { state: Flagged(MineKind::Secondary), .. }) => {
+                            String::from("🏴")
+                        }
+                        (_, Number { state: Flagged(_), .. } | Mine { state: Flagged(_), .. }) => {
+                            String::from("🚩")
+                        }
+                        (_, Number { state: Closed, .. } | Mine { state: Closed, .. }) => String::from("❓"),
+                        (_, Number { state: Questioned, .. } | Mine { state: Questioned, .. }) => String::from("❔"),
+                        (_, Number { .. }) if !props.has_surrounding_mines => String::from(""),
+                        (_, Number { count, secondary_count, .. }) if props.variant == Variant::TwoColour => {
+                            format!("{}·{}", count, secondary_count)
+                        }
+                        (_, Number { count, .. }) => format!("{}",count),
                     }
                 }
             </div>